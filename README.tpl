@@ -10,6 +10,12 @@ Sample run with default threshold (0.75).
 RUST_LOG=info cargo run -- --language rust --project-count 50
 ```
 
+Full report of every analyzed repository streamed as NDJSON to a file (`--output-format` can also be `json`, `csv` or `markdown`).
+
+```shell
+cargo run -- --language rust --project-count 50 --full-report --output-format ndjson --output-file report.ndjson
+```
+
 Use `--help` for other params (like `--api-token` with `API_TOKEN` env variable).
 
 Environment variables can be configured using `.env` in working dir (*for Cargo run it is `target/debug`*)
@@ -0,0 +1,193 @@
+use crate::api::{Contributor, Error, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Maps alternative logins or emails of contributors to their canonical names,
+/// so that contributions of the same person are not split.
+#[derive(Debug, Clone, Default)]
+pub struct ContributorAliases {
+    /// Canonical names by lowercase aliases
+    canonical: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct AliasTable {
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl ContributorAliases {
+    /// Parses `.mailmap` content. Every line maps the last `<identity>` (commit email, or login) to the proper name,
+    /// or to the proper `<identity>` if there is no name, e.g.:
+    /// ```text
+    /// Alice <alice@example.com> <alice@old.example.com>
+    /// <alice> <alice-work>
+    /// ```
+    pub fn from_mailmap(mailmap: &str) -> Result<Self> {
+        let mut aliases = ContributorAliases::default();
+        for (line_no, line) in mailmap.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let entry = MailmapEntry::parse(line)
+                .ok_or_else(|| Error::Error(format!("Invalid mailmap line {}: {}", line_no + 1, line)))?;
+            let canonical = entry.proper_name.unwrap_or(entry.proper_identity);
+            aliases.insert(entry.proper_identity, canonical);
+            if let Some(commit_identity) = entry.commit_identity {
+                aliases.insert(commit_identity, canonical);
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Parses TOML `aliases` table of canonical names with lists of their aliases, e.g.:
+    /// ```toml
+    /// [aliases]
+    /// alice = ["alice-work", "alice@example.com"]
+    /// ```
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let table: AliasTable =
+            toml::from_str(toml).map_err(|err| Error::Error(format!("Invalid alias table: {}", err)))?;
+        let mut aliases = ContributorAliases::default();
+        for (canonical, names) in &table.aliases {
+            for name in names {
+                aliases.insert(name, canonical);
+            }
+        }
+        Ok(aliases)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
+
+    /// Canonical name of contributor. Aliases are matched case insensitively.
+    pub fn canonical<'a>(&'a self, name: &'a str) -> &'a str {
+        self.canonical.get(&name.to_lowercase()).map_or(name, String::as_str)
+    }
+
+    /// Merges contributions of aliases under canonical names.
+    /// Returns contributors sorted by contributions in desc order, keeping order of first occurrence for equal ones.
+    pub fn merge(&self, contributors: Vec<Contributor>) -> Vec<Contributor> {
+        if self.is_empty() {
+            return contributors;
+        }
+        let mut merged: Vec<Contributor> = Vec::new();
+        let mut positions = HashMap::new();
+        for contributor in contributors {
+            let name = self.canonical(&contributor.name).to_string();
+            match positions.get(&name) {
+                Some(&position) => {
+                    let merged_contributor: &mut Contributor = &mut merged[position];
+                    merged_contributor.contributions += contributor.contributions;
+                    merged_contributor.bot &= contributor.bot;
+                }
+                None => {
+                    positions.insert(name.clone(), merged.len());
+                    merged.push(Contributor { name, ..contributor });
+                }
+            }
+        }
+        merged.sort_by_key(|contributor| std::cmp::Reverse(contributor.contributions));
+        merged
+    }
+
+    fn insert(&mut self, alias: &str, canonical: &str) {
+        if alias != canonical {
+            self.canonical.insert(alias.to_lowercase(), canonical.to_string());
+        }
+    }
+}
+
+struct MailmapEntry<'a> {
+    proper_name: Option<&'a str>,
+    proper_identity: &'a str,
+    commit_identity: Option<&'a str>,
+}
+
+impl<'a> MailmapEntry<'a> {
+    /// Parses `Proper Name <proper identity> Commit Name <commit identity>` with optional names and commit part.
+    fn parse(line: &'a str) -> Option<Self> {
+        let (proper_name, proper_identity, rest) = split_identity(line)?;
+        let commit_identity = match rest.trim() {
+            "" => None,
+            rest => Some(split_identity(rest)?.1),
+        };
+        Some(MailmapEntry {
+            proper_name,
+            proper_identity,
+            commit_identity,
+        })
+    }
+}
+
+/// Splits `Name <identity> rest` into optional name, identity and rest.
+fn split_identity(value: &str) -> Option<(Option<&str>, &str, &str)> {
+    let (name, rest) = value.split_once('<')?;
+    let (identity, rest) = rest.split_once('>')?;
+    let name = Some(name.trim()).filter(|name| !name.is_empty());
+    Some((name, identity.trim(), rest))
+}
+
+// Tests
+
+#[test]
+fn mailmap_test() -> Result<()> {
+    let aliases = ContributorAliases::from_mailmap(
+        r#"
+        # comment
+        Alice <alice@example.com> <alice@old.example.com>
+        Alice <alice@example.com> Alice Work <ALICE@work.example.com>
+        <bob> <bob-work> # trailing comment
+        Carol <carol@example.com>
+        "#,
+    )?;
+    assert_eq!(aliases.canonical("alice@old.example.com"), "Alice");
+    assert_eq!(aliases.canonical("alice@work.example.com"), "Alice");
+    assert_eq!(aliases.canonical("alice@example.com"), "Alice");
+    assert_eq!(aliases.canonical("bob-work"), "bob");
+    assert_eq!(aliases.canonical("carol@example.com"), "Carol");
+    assert_eq!(aliases.canonical("dave"), "dave");
+    assert!(ContributorAliases::from_mailmap("Alice alice@example.com").is_err());
+    Ok(())
+}
+
+#[test]
+fn toml_test() -> Result<()> {
+    let aliases = ContributorAliases::from_toml(
+        r#"
+        [aliases]
+        alice = ["alice-work", "alice@example.com"]
+        "#,
+    )?;
+    assert_eq!(aliases.canonical("Alice-Work"), "alice");
+    assert_eq!(aliases.canonical("alice@example.com"), "alice");
+    assert!(ContributorAliases::from_toml("aliases = 1").is_err());
+    Ok(())
+}
+
+#[test]
+fn merge_test() -> Result<()> {
+    let aliases = ContributorAliases::from_mailmap("<alice> <alice-work>\n<bob> <bob@example.com>")?;
+    let contributors = vec![
+        Contributor::new("carol", 5),
+        Contributor::new("alice", 4),
+        Contributor::new("bob", 3),
+        Contributor::new("alice-work", 2),
+        Contributor::new("bob@example.com", 1),
+    ];
+    let merged = aliases
+        .merge(contributors)
+        .into_iter()
+        .map(|c| (c.name, c.contributions))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        merged,
+        vec![
+            ("alice".to_string(), 6),
+            ("carol".to_string(), 5),
+            ("bob".to_string(), 4)
+        ]
+    );
+    Ok(())
+}
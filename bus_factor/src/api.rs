@@ -1,19 +1,117 @@
 use async_trait::async_trait;
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
 use strum_macros::{AsRefStr, EnumString};
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
 pub enum Error {
     #[error("Error: {0}")]
     Error(String),
-    // the only reason of `reqwest` dependency..
+    /// Rate limit exceeded. `reset` is UTC epoch seconds when limit resets, if known.
+    #[error("Rate limited{}: {context}", .reset.map_or_else(String::new, |reset| format!(" until {}", reset)))]
+    RateLimited {
+        reset: Option<i64>,
+        context: RequestContext,
+    },
+    #[error("Unauthorized: {0}")]
+    Unauthorized(RequestContext),
+    #[error("Forbidden: {0}")]
+    Forbidden(RequestContext),
+    #[error("Not found: {0}")]
+    NotFound(RequestContext),
+    #[error("Server error: {0}")]
+    ServerError(RequestContext),
+    /// Response with other unsuccessful status
     #[error("Request error: {0}")]
-    RequestError(String),
-    // the only reason of `reqwest` dependency..
+    RequestError(RequestContext),
+    /// Connection failure, before any response status was received
+    #[error("Transport error: {0}")]
+    Transport(RequestContext),
+    #[error("Timeout: {0}")]
+    Timeout(RequestContext),
+    /// Response body could not be decoded
+    #[error("Decode error: {0}")]
+    Decode(RequestContext),
     #[error("Client error: {0}")]
     ClientError(#[from] anyhow::Error),
-    // the only reason of `reqwest` dependency..
+}
+
+impl Error {
+    /// Request context of errors related to a request.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Error::RateLimited { context, .. }
+            | Error::Unauthorized(context)
+            | Error::Forbidden(context)
+            | Error::NotFound(context)
+            | Error::ServerError(context)
+            | Error::RequestError(context)
+            | Error::Transport(context)
+            | Error::Timeout(context)
+            | Error::Decode(context) => Some(context),
+            Error::Error(_) | Error::ClientError(_) => None,
+        }
+    }
+
+    /// HTTP status of response, if any.
+    pub fn status(&self) -> Option<u16> {
+        self.context().and_then(|context| context.status)
+    }
+}
+
+/// Client error is cloned as its message, e.g. to report failure of a batched request to each of its callers.
+impl Clone for Error {
+    fn clone(&self) -> Self {
+        match self {
+            Error::Error(message) => Error::Error(message.clone()),
+            Error::RateLimited { reset, context } => Error::RateLimited {
+                reset: *reset,
+                context: context.clone(),
+            },
+            Error::Unauthorized(context) => Error::Unauthorized(context.clone()),
+            Error::Forbidden(context) => Error::Forbidden(context.clone()),
+            Error::NotFound(context) => Error::NotFound(context.clone()),
+            Error::ServerError(context) => Error::ServerError(context.clone()),
+            Error::RequestError(context) => Error::RequestError(context.clone()),
+            Error::Transport(context) => Error::Transport(context.clone()),
+            Error::Timeout(context) => Error::Timeout(context.clone()),
+            Error::Decode(context) => Error::Decode(context.clone()),
+            Error::ClientError(error) => Error::ClientError(anyhow::anyhow!(error.to_string())),
+        }
+    }
+}
+
+/// Context of failed request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestContext {
+    pub url: Option<String>,
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl RequestContext {
+    pub fn new(url: Option<String>, status: Option<u16>, message: impl Into<String>) -> Self {
+        RequestContext {
+            url,
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for RequestContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(status) = self.status {
+            write!(f, " (status {})", status)?;
+        }
+        if let Some(url) = &self.url {
+            write!(f, " [{}]", url)?;
+        }
+        Ok(())
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -21,11 +119,36 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub trait Repo: Send + Sync + Debug {
     type T: Into<String> + Display;
     fn name(&self) -> Self::T;
+
+    /// Repository metadata reported together with its bus factor.
+    fn details(&self) -> RepoDetails {
+        RepoDetails::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RepoDetails {
+    pub owner: String,
+    /// Name including owner, e.g. `owner/repo`
+    pub full_name: String,
+    pub html_url: String,
+    pub stars: u32,
+    pub forks: u32,
+    pub open_issues: u32,
+    pub default_branch: String,
+    /// Time of last push in ISO 8601 format
+    pub pushed_at: Option<String>,
+    pub archived: bool,
+    pub fork: bool,
 }
 
+#[derive(Debug, Clone)]
 pub struct Contributor {
     pub name: String,
     pub contributions: u32,
+    /// Whether account is reported by the repository service as a bot
+    pub bot: bool,
 }
 
 impl Contributor {
@@ -33,6 +156,23 @@ impl Contributor {
         Contributor {
             name: name.into(),
             contributions,
+            bot: false,
+        }
+    }
+}
+
+/// Page of contributors.
+pub struct ContributorsPage {
+    pub contributors: Vec<Contributor>,
+    /// Service does not list contributors beyond this page, even if there are more
+    pub truncated: bool,
+}
+
+impl From<Vec<Contributor>> for ContributorsPage {
+    fn from(contributors: Vec<Contributor>) -> Self {
+        ContributorsPage {
+            contributors,
+            truncated: false,
         }
     }
 }
@@ -44,10 +184,39 @@ pub trait Client<REPO: Repo, const MAX_REPOS_PAGE: u32, const MAX_CONTRIBUTORS_P
 {
     async fn top_repos(&self, lang: String, page: u32, per_page: u32, order: Sort) -> Result<Vec<REPO>>;
 
-    async fn top_contributors(&self, contributor: &'_ REPO, page: u32, per_page: u32) -> Result<Vec<Contributor>>;
+    /// Returns empty page for empty repository.
+    async fn top_contributors(&self, contributor: &'_ REPO, page: u32, per_page: u32) -> Result<ContributorsPage>;
+
+    /// Ratio of remaining requests of given kind to their rate limit. `None` if client is not rate limited.
+    async fn remaining_rate_limit(&self, _kind: RequestKind) -> Option<f32> {
+        None
+    }
+
+    /// Statistics of requests by API resource (e.g. `search`, `core`).
+    fn stats(&self) -> BTreeMap<String, ResourceStats> {
+        BTreeMap::new()
+    }
+}
+
+/// Statistics of requests to API resource.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ResourceStats {
+    /// Number of sent requests, including retries
+    pub requests: u64,
+    /// Time spent waiting for rate limits, in milliseconds
+    pub rate_limit_wait_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    /// `Client::top_repos` request
+    Repos,
+    /// `Client::top_contributors` request
+    Contributors,
 }
 
-#[derive(Debug, EnumString, Clone, AsRefStr)]
+#[derive(Debug, EnumString, Clone, AsRefStr, PartialEq, Eq)]
 pub enum Sort {
     #[strum(serialize = "stars")]
     Stars,
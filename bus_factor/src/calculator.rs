@@ -1,24 +1,224 @@
-use crate::api::{Client, Contributor, Repo};
-use crate::api::{Error, Sort};
+use crate::alias::ContributorAliases;
+use crate::api::{Client, Contributor, Repo, RepoDetails};
+use crate::api::{Error, RequestKind, Sort};
+use crate::concurrency::{AdaptiveConcurrency, Observation, Permit};
+use crate::filter::ContributorFilter;
+use crate::metric::BusFactorMetric;
+use crate::summary::RunStats;
+use crate::task::AbortOnDrop;
+use crate::wait::with_waits;
 use derive_more::Constructor;
 use futures::task::Poll;
-use futures::{stream, Stream, StreamExt};
-use log::error;
+use futures::{future, stream, FutureExt, Stream, StreamExt};
+use log::{debug, info};
 use std::fmt::Debug;
 use std::ops::AddAssign;
 use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Duration;
 use std::{marker::PhantomData, sync::Arc};
+use strum_macros::AsRefStr;
+use thiserror::Error;
 use tokio::task::JoinError;
-use tokio::task::JoinHandle;
+use tokio::time::Instant;
 
-#[derive(Debug, PartialEq, Constructor)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BusFactor {
     pub repo: String,
-    pub contributor: String,
-    pub percentage: f32,
+    /// Position of repository in search results, starting from 1
+    pub rank: u32,
+    pub details: RepoDetails,
+    /// Number of key contributors, 0 if every contributor has been excluded
+    pub factor: u32,
+    /// Key contributors sorted by contributions in desc order
+    pub contributors: Vec<String>,
+    /// Metric score from range [0.0,1.0]
+    pub score: f32,
+    /// Whether bus factor condition of the metric has been met
+    pub reached: bool,
+    /// Contributors excluded from calculation by `ContributorFilter`
+    pub excluded: Vec<String>,
+    /// Whether repository service has not listed all contributors
+    pub truncated: bool,
+    /// Total contributions of contributors taken into account
+    pub total_contributions: u32,
+    /// Number of contributors taken into account
+    pub contributor_count: u32,
 }
 
-pub type BusFactorStream = Pin<Box<dyn Stream<Item = BusFactor> + std::marker::Send>>;
+impl BusFactor {
+    pub fn new(repo: String, factor: u32, contributors: Vec<String>, score: f32) -> Self {
+        BusFactor {
+            repo,
+            rank: 0,
+            details: RepoDetails::default(),
+            factor,
+            contributors,
+            score,
+            reached: true,
+            excluded: Vec::new(),
+            truncated: false,
+            total_contributions: 0,
+            contributor_count: 0,
+        }
+    }
+
+    pub fn with_details(mut self, details: RepoDetails) -> Self {
+        self.details = details;
+        self
+    }
+
+    pub fn with_rank(mut self, rank: u32) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    pub fn with_reached(mut self, reached: bool) -> Self {
+        self.reached = reached;
+        self
+    }
+
+    pub fn with_excluded(mut self, excluded: Vec<String>) -> Self {
+        self.excluded = excluded;
+        self
+    }
+
+    pub fn with_truncated(mut self, truncated: bool) -> Self {
+        self.truncated = truncated;
+        self
+    }
+
+    /// Sets contributions and number of contributors taken into account.
+    pub fn with_contributors_stats(mut self, contributors: &[Contributor]) -> Self {
+        self.total_contributions = total_contributions(contributors);
+        self.contributor_count = contributors.len() as u32;
+        self
+    }
+}
+
+/// Reason why repository could not be analyzed.
+#[derive(Debug, Error, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Failure {
+    #[error("Search page {page} failed: {error}")]
+    SearchPageFailed { page: u32, repos: u32, error: Error },
+    #[error("Contributors of {repo} forbidden: {error}")]
+    ContributorsForbidden { repo: String, error: Error },
+    #[error("Contributors of {repo} failed: {error}")]
+    ContributorsFailed { repo: String, error: Error },
+    #[error("Repository {repo} is empty")]
+    RepoEmpty { repo: String },
+    #[error("Task panicked: {0}")]
+    TaskPanicked(String),
+    #[error("Deadline of {} sec exceeded", .0.as_secs())]
+    DeadlineExceeded(Duration),
+}
+
+impl Failure {
+    /// Number of repositories not analyzed because of the failure.
+    pub fn failed_repos(&self) -> u32 {
+        match self {
+            Failure::SearchPageFailed { repos, .. } => *repos,
+            Failure::DeadlineExceeded(_) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Error causing the failure, if any.
+    pub fn error(&self) -> Option<&Error> {
+        match self {
+            Failure::SearchPageFailed { error, .. }
+            | Failure::ContributorsForbidden { error, .. }
+            | Failure::ContributorsFailed { error, .. } => Some(error),
+            Failure::RepoEmpty { .. } | Failure::TaskPanicked(_) | Failure::DeadlineExceeded(_) => None,
+        }
+    }
+
+    fn contributors(repo: String, error: Error) -> Self {
+        match error {
+            error @ Error::Forbidden(_) => Failure::ContributorsForbidden { repo, error },
+            error => Failure::ContributorsFailed { repo, error },
+        }
+    }
+}
+
+pub type BusFactorResult = Result<BusFactor, Failure>;
+pub type BusFactorStream = Pin<Box<dyn Stream<Item = BusFactorResult> + std::marker::Send>>;
+type JoinHandleStream<T> = Pin<Box<dyn Stream<Item = AbortOnDrop<T>> + Send>>;
+
+/// Range of repository contributors taken into account.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ContributorsWindow {
+    /// Top N contributors
+    Top(u32),
+    /// All contributors
+    All,
+    /// Contributors pages are fetched until contributions of previously fetched contributors make given ratio
+    /// of contributions fetched so far (including last page), i.e. until a page adds little (diminishing returns).
+    /// Total contributions are not known, so it does not guarantee fetched contributors cover given ratio of them.
+    Saturation(f32),
+}
+
+impl ContributorsWindow {
+    fn page_size(&self, max_page_size: u32) -> u32 {
+        match self {
+            ContributorsWindow::Top(n) => std::cmp::min(*n, max_page_size),
+            _ => max_page_size,
+        }
+    }
+
+    /// Maximal number of contributors not fetched yet, if known.
+    fn remaining(&self, contributors: &[Contributor]) -> Option<u32> {
+        match self {
+            ContributorsWindow::Top(n) => Some(n.saturating_sub(contributors.len() as u32)),
+            _ => None,
+        }
+    }
+
+    fn is_complete(&self, contributors: &[Contributor], previous_contributions: u32) -> bool {
+        match self {
+            ContributorsWindow::Top(n) => contributors.len() as u32 >= *n,
+            ContributorsWindow::All => false,
+            ContributorsWindow::Saturation(ratio) => {
+                let contributions = total_contributions(contributors);
+                contributions > 0 && previous_contributions as f32 / contributions as f32 >= *ratio
+            }
+        }
+    }
+}
+
+impl Default for ContributorsWindow {
+    fn default() -> Self {
+        ContributorsWindow::Top(25)
+    }
+}
+
+/// Parses `all`, percentage of contributions saturation (e.g. `90%`), or number of top contributors.
+impl FromStr for ContributorsWindow {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "all" {
+            return Ok(ContributorsWindow::All);
+        }
+        if let Some(percentage) = value.strip_suffix('%') {
+            return match percentage.parse::<f32>() {
+                Ok(percentage) if percentage > 0.0 && percentage <= 100.0 => {
+                    Ok(ContributorsWindow::Saturation(percentage / 100.0))
+                }
+                _ => Err(format!("{} is not a percentage in range 0% .. 100%.", value)),
+            };
+        }
+        match value.parse::<u32>() {
+            Ok(n) if n > 0 => Ok(ContributorsWindow::Top(n)),
+            _ => Err(format!(
+                "{} is neither `all`, a percentage, nor a positive number.",
+                value
+            )),
+        }
+    }
+}
 pub struct BusFactorCalculator<
     REPO,
     const MAX_REPOS_PAGE: u32,
@@ -30,7 +230,12 @@ pub struct BusFactorCalculator<
     CLIENT: 'static + Client<REPO, MAX_REPOS_PAGE, MAX_CONTRIBUTORS_PAGE, FIRST_PAGE_NUMBER>,
 {
     client: Arc<CLIENT>,
-    threshold: f32,
+    settings: ContributorsSettings,
+    full_report: bool,
+    adaptive_concurrency: bool,
+    unordered: bool,
+    deadline: Option<Duration>,
+    stats: RunStats,
     _repo_type: PhantomData<REPO>,
 }
 
@@ -40,30 +245,134 @@ where
     REPO: 'static + Repo,
     CLIENT: 'static + Client<REPO, MAX_REPOS_PAGE, MAX_CONTRIBUTORS_PAGE, FIRST_PAGE_NUMBER>,
 {
-    pub fn new(client: CLIENT, threshold: f32) -> Self {
-        let _repo_type = PhantomData::default();
+    /// # Arguments
+    /// * `metric` - metric deciding which repositories have a bus factor
+    pub fn new(client: CLIENT, metric: impl BusFactorMetric + 'static) -> Self {
+        let _repo_type = PhantomData;
+        let client = Arc::new(client);
+        let stats_client = client.clone();
         BusFactorCalculator {
-            client: Arc::new(client),
-            threshold,
+            client,
+            settings: ContributorsSettings {
+                metric: Arc::new(metric),
+                window: ContributorsWindow::default(),
+                early_exit: true,
+                filter: ContributorFilter::default(),
+                aliases: ContributorAliases::default(),
+                concurrency: None,
+            },
+            full_report: false,
+            adaptive_concurrency: false,
+            unordered: false,
+            deadline: None,
+            stats: RunStats::new(Arc::new(move || stats_client.stats())),
             _repo_type,
         }
     }
 
+    pub fn with_contributors_window(mut self, contributors_window: ContributorsWindow) -> Self {
+        self.settings.window = contributors_window;
+        self
+    }
+
+    /// Excludes contributors before they are measured. `ContributorFilter::default()` by default.
+    pub fn with_contributor_filter(mut self, filter: ContributorFilter) -> Self {
+        self.settings.filter = filter;
+        self
+    }
+
+    /// Merges contributions of aliases before they are measured. Disables early exit of paging contributors,
+    /// because aliases of fetched contributors may appear on next pages.
+    pub fn with_contributor_aliases(mut self, aliases: ContributorAliases) -> Self {
+        self.settings.aliases = aliases;
+        self
+    }
+
+    /// Stop paging contributors once `BusFactorMetric::is_settled`. Enabled by default, unless full report is.
+    pub fn with_early_exit(mut self, early_exit: bool) -> Self {
+        self.settings.early_exit = early_exit;
+        self
+    }
+
+    /// Report every analyzed repository, including those which have not reached bus factor condition. Disabled by default.
+    /// Disables early exit of paging contributors, so reported statistics cover every contributor in window.
+    pub fn with_full_report(mut self, full_report: bool) -> Self {
+        self.full_report = full_report;
+        self
+    }
+
+    /// Adjust number of parallel requests to their latency, failures and remaining rate limit,
+    /// up to `max_repo_requests` and `max_contrib_requests` of `calculate`. Disabled by default.
+    pub fn with_adaptive_concurrency(mut self, adaptive_concurrency: bool) -> Self {
+        self.adaptive_concurrency = adaptive_concurrency;
+        self
+    }
+
+    /// Emit results as soon as they are calculated, instead of in search order. Disabled by default.
+    /// Search order can be restored with `BusFactor::rank`.
+    pub fn with_unordered(mut self, unordered: bool) -> Self {
+        self.unordered = unordered;
+        self
+    }
+
+    /// Ends results with `Failure::DeadlineExceeded` once given time since `calculate` passes,
+    /// aborting requests in progress. No deadline by default.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Statistics of the run, updated while results of `calculate` are consumed.
+    pub fn stats(&self) -> RunStats {
+        self.stats.clone()
+    }
+
+    /// Tasks of results are aborted when returned stream is dropped.
     pub fn calculate(
-        self,
+        mut self,
         lang: String,
         repo_count: u32,
         max_repo_requests: usize,
         max_contrib_requests: usize,
         order: Sort,
     ) -> BusFactorStream {
-        Self::top_repos(self.client.clone(), lang, repo_count, order)
+        let full_report = self.full_report;
+        self.settings.early_exit &= !full_report;
+        let stats = self.stats.clone();
+        stats.start(repo_count);
+        let repos_concurrency = self
+            .adaptive_concurrency
+            .then(|| Arc::new(AdaptiveConcurrency::new(1, max_repo_requests)));
+        self.settings.concurrency = self
+            .adaptive_concurrency
+            .then(|| Arc::new(AdaptiveConcurrency::new(1, max_contrib_requests)));
+        let bus_factors = Self::top_repos(self.client.clone(), lang, repo_count, order, repos_concurrency)
             .buffered(max_repo_requests)
             .flat_map(Self::map_top_repos_result)
-            .map(move |r| Self::repo_bus_factor(r, self.client.clone(), self.threshold))
-            .buffered(max_contrib_requests)
-            .filter_map(map_bus_factor_result)
-            .boxed()
+            .scan(0, |rank, repo| future::ready(Some(ranked(rank, repo))))
+            .map(move |repo| match repo {
+                Ok((rank, repo)) => {
+                    self.stats.searched();
+                    Self::repo_bus_factor(rank, repo, self.client.clone(), self.settings.clone())
+                        .map(map_bus_factor_result)
+                        .boxed()
+                }
+                Err(failure) => future::ready(Err(failure)).boxed(),
+            });
+        let bus_factors: BusFactorStream = match self.unordered {
+            true => bus_factors.buffer_unordered(max_contrib_requests).boxed(),
+            false => bus_factors.buffered(max_contrib_requests).boxed(),
+        };
+        let reported_stats = stats.clone();
+        let bus_factors = bus_factors
+            .inspect(move |result| stats.calculated(result))
+            .filter(move |result| future::ready(full_report || result.as_ref().map_or(true, |bf| bf.reached)))
+            .inspect(move |result| reported_stats.reported(result))
+            .boxed();
+        match self.deadline {
+            Some(deadline) => with_deadline(bus_factors, deadline),
+            None => bus_factors,
+        }
     }
 
     fn top_repos(
@@ -71,13 +380,15 @@ where
         lang: String,
         repo_count: u32,
         order: Sort,
-    ) -> Pin<Box<dyn Stream<Item = JoinHandle<Result<Vec<REPO>, crate::api::Error>>> + Send>> {
+        concurrency: Option<Arc<AdaptiveConcurrency>>,
+    ) -> JoinHandleStream<Result<Vec<REPO>, Failure>> {
         let mut paginator = Paginator::new(FIRST_PAGE_NUMBER, MAX_REPOS_PAGE, repo_count);
         stream::poll_fn(move |_| Poll::Ready(paginator.next_page()))
             .map(move |page| {
                 let client = client.clone();
                 let lang = lang.clone();
-                tokio::spawn(Self::top_repos_page(client, lang, page, order.clone()))
+                let concurrency = concurrency.clone();
+                crate::task::spawn(Self::top_repos_page(client, lang, page, order.clone(), concurrency))
             })
             .boxed()
     }
@@ -87,91 +398,252 @@ where
         lang: String,
         page: Page,
         order: Sort,
+        concurrency: Option<Arc<AdaptiveConcurrency>>,
+    ) -> Result<Vec<REPO>, Failure> {
+        let page_no = page.page_no;
+        let repos = page.page_size;
+        let _permit = acquire(&concurrency).await;
+        let started = Instant::now();
+        let (result, waited) = with_waits(Self::fetch_top_repos_page(client.clone(), lang, page, order)).await;
+        let latency = started.elapsed().saturating_sub(waited);
+        Self::observe(client.as_ref(), &concurrency, RequestKind::Repos, latency, &result).await;
+        result.map_err(|error| Failure::SearchPageFailed {
+            page: page_no,
+            repos,
+            error,
+        })
+    }
+
+    async fn fetch_top_repos_page(
+        client: Arc<CLIENT>,
+        lang: String,
+        page: Page,
+        order: Sort,
     ) -> crate::api::Result<Vec<REPO>> {
         if page.page_size < MAX_REPOS_PAGE {
             if page.page_no == FIRST_PAGE_NUMBER {
-                client.top_repos(lang.into(), page.page_no, page.page_size, order).await
+                client.top_repos(lang, page.page_no, page.page_size, order).await
             } else {
-                let repos = client.top_repos(lang.into(), page.page_no, MAX_REPOS_PAGE, order).await;
+                let repos = client.top_repos(lang, page.page_no, MAX_REPOS_PAGE, order).await;
                 repos.map(|v| take_first_n(v, page.page_size))
             }
         } else {
-            client.top_repos(lang.into(), page.page_no, page.page_size, order).await
+            client.top_repos(lang, page.page_no, page.page_size, order).await
         }
     }
 
-    fn repo_bus_factor(repo: REPO, client: Arc<CLIENT>, threshold: f32) -> JoinHandle<Option<BusFactor>> {
-        // TODO add parameter for 'per_page'
-        let client = client.clone();
-        tokio::spawn(async move {
-            client
-                .top_contributors(&repo, FIRST_PAGE_NUMBER, 25)
+    fn repo_bus_factor(
+        rank: u32,
+        repo: REPO,
+        client: Arc<CLIENT>,
+        settings: ContributorsSettings,
+    ) -> AbortOnDrop<BusFactorResult> {
+        crate::task::spawn(async move {
+            let _permit = acquire(&settings.concurrency).await;
+            let repo_name: String = repo.name().into();
+            let fetched = Self::top_contributors(client.as_ref(), &repo, &settings)
                 .await
-                .map(|contributors| contributors_bus_factor(contributors, repo.name().into(), threshold))
-                .unwrap_or_else(|err| {
-                    error!("Failed to get top contributors: {}", err);
-                    None
-                })
+                .map_err(|error| Failure::contributors(repo_name.clone(), error))?;
+            let contributors = &fetched.contributors;
+            let bus_factor = match contributors_bus_factor(contributors, repo_name.clone(), settings.metric.as_ref()) {
+                Some(bus_factor) => bus_factor,
+                // Not a failure, repository has only contributors excluded by filter
+                None if !fetched.excluded.is_empty() => {
+                    BusFactor::new(repo_name, 0, Vec::new(), 0.0).with_reached(false)
+                }
+                None => return Err(Failure::RepoEmpty { repo: repo_name }),
+            };
+            Ok(bus_factor
+                .with_rank(rank)
+                .with_details(repo.details())
+                .with_contributors_stats(contributors)
+                .with_excluded(fetched.excluded)
+                .with_truncated(fetched.truncated))
         })
     }
 
-    /// Utility functions
+    /// Fetches contributors pages until contributors window is complete, metric result is settled,
+    /// or there are no more (listed) contributors.
+    async fn top_contributors(
+        client: &CLIENT,
+        repo: &REPO,
+        settings: &ContributorsSettings,
+    ) -> crate::api::Result<FetchedContributors> {
+        let contributors_window = settings.window;
+        let page_size = contributors_window.page_size(MAX_CONTRIBUTORS_PAGE);
+        let mut contributors = Vec::new();
+        let mut excluded = Vec::new();
+        let mut truncated = false;
+        let mut page_no = FIRST_PAGE_NUMBER;
+        loop {
+            let started = Instant::now();
+            let (page, waited) = with_waits(client.top_contributors(repo, page_no, page_size)).await;
+            let latency = started.elapsed().saturating_sub(waited);
+            Self::observe(client, &settings.concurrency, RequestKind::Contributors, latency, &page).await;
+            let page = page?;
+            truncated |= page.truncated;
+            let last_page = (page.contributors.len() as u32) < page_size || page.truncated;
+            let (page, excluded_page) = settings.filter.partition(page.contributors);
+            excluded.extend(excluded_page.into_iter().map(|contributor| contributor.name));
+            let previous_contributions = total_contributions(&contributors);
+            contributors.extend(page);
+            if last_page || contributors_window.is_complete(&contributors, previous_contributions) {
+                break;
+            }
+            if settings.early_exit && settings.is_settled(&contributors) {
+                debug!("Contributors of {} settled after {} pages", repo.name(), page_no);
+                break;
+            }
+            page_no.add_assign(1);
+        }
+        let mut contributors = settings.aliases.merge(contributors);
+        if let ContributorsWindow::Top(n) = contributors_window {
+            contributors.truncate(n as usize);
+        }
+        if !excluded.is_empty() {
+            info!("Excluded contributors of {}: {}", repo.name(), excluded.join(", "));
+        }
+        if truncated {
+            info!("Contributors of {} truncated after {} pages", repo.name(), page_no);
+        }
+        Ok(FetchedContributors {
+            contributors,
+            excluded,
+            truncated,
+        })
+    }
 
-    fn map_top_repos_result(repos: Result<Result<Vec<REPO>, Error>, JoinError>) -> impl Stream<Item = REPO> {
-        if let Ok(Ok(repos)) = repos {
-            return stream::iter(repos);
+    /// Reports request outcome to adaptive concurrency, if enabled.
+    /// Latency excludes waits of client for rate limits and retries, which do not mean the service slows down.
+    async fn observe<T>(
+        client: &CLIENT,
+        concurrency: &Option<Arc<AdaptiveConcurrency>>,
+        kind: RequestKind,
+        latency: Duration,
+        result: &crate::api::Result<T>,
+    ) {
+        if let Some(concurrency) = concurrency {
+            concurrency.observe(Observation {
+                latency,
+                error: result.as_ref().err(),
+                remaining_rate_limit: client.remaining_rate_limit(kind).await,
+            });
         }
-        match repos {
-            Ok(Err(err)) => error!("Failed to get top repositories: {:?}", err),
-            err => error!("Failed to get top repositories: {:?}", err),
+    }
+
+    // Utility functions
+
+    fn map_top_repos_result(
+        repos: Result<Result<Vec<REPO>, Failure>, JoinError>,
+    ) -> impl Stream<Item = Result<REPO, Failure>> {
+        let repos = match repos {
+            Ok(Ok(repos)) => repos.into_iter().map(Ok).collect(),
+            Ok(Err(failure)) => vec![Err(failure)],
+            Err(err) => vec![Err(Failure::TaskPanicked(err.to_string()))],
+        };
+        stream::iter(repos)
+    }
+}
+
+struct FetchedContributors {
+    /// Contributors not excluded by filter
+    contributors: Vec<Contributor>,
+    /// Names of contributors excluded by filter
+    excluded: Vec<String>,
+    /// Whether last fetched page was truncated
+    truncated: bool,
+}
+
+#[derive(Clone)]
+struct ContributorsSettings {
+    metric: Arc<dyn BusFactorMetric>,
+    window: ContributorsWindow,
+    early_exit: bool,
+    filter: ContributorFilter,
+    aliases: ContributorAliases,
+    /// Limits number of repositories with contributors fetched in parallel, if adaptive concurrency is enabled
+    concurrency: Option<Arc<AdaptiveConcurrency>>,
+}
+
+impl ContributorsSettings {
+    /// Saturation window depends on fetched contributions, so it is never settled early.
+    /// Neither are merged aliases, because contributions of already fetched contributors may still grow.
+    fn is_settled(&self, contributors: &[Contributor]) -> bool {
+        match self.window {
+            _ if !self.aliases.is_empty() => false,
+            ContributorsWindow::Saturation(_) => false,
+            window => self.metric.is_settled(contributors, window.remaining(contributors)),
         }
-        stream::iter(Vec::new()) //TODO how to return stream::empty() ???
     }
 }
 
-/// Returns `RepoBusFactor` if `threshold` reached.
+/// Returns `BusFactor` of repository, `reached` if `metric` condition reached.
+/// Returns `None` if there are no contributions.
 ///
 /// # Arguments
 /// * `contributors` - List of `Contributor`s sorted by contributions in desc order
 /// * `repo` - Name of repository
-/// * `threshold` - contribution ratio threshold of top(first) contributor to total contributions of listed `contributors`
-fn contributors_bus_factor(contributors: Vec<Contributor>, repo: String, threashold: f32) -> Option<BusFactor> {
-    let top_contributor = contributors.get(0)?;
-    let total_contributions = contributors
-        .iter()
-        .map(|contributor| contributor.contributions)
-        .fold(0, |acc, c| acc + c);
-    let bus_factor = calculate_percentage(top_contributor.contributions, total_contributions);
-    if bus_factor >= threashold {
-        Some(BusFactor::new(repo, top_contributor.name.to_string(), bus_factor))
-    } else {
-        None
-    }
+/// * `metric` - contributions concentration metric
+fn contributors_bus_factor(
+    contributors: &[Contributor],
+    repo: String,
+    metric: &dyn BusFactorMetric,
+) -> Option<BusFactor> {
+    metric.measure(contributors).map(|measurement| {
+        let factor = measurement.contributors.len() as u32;
+        BusFactor::new(repo, factor, measurement.contributors, measurement.score).with_reached(measurement.reached)
+    })
 }
 
-/// Produces float from range [0.0,1.1] rounded to two decimal points.
-fn calculate_percentage(contributions: u32, total_contributions: u32) -> f32 {
-    let bus_factor = contributions as f32 / total_contributions as f32;
-    (&format!("{0:.1$}", bus_factor, 2)).parse().unwrap() //TODO probably there is a smarter way to do this...
+// Utility functions
+
+fn total_contributions(contributors: &[Contributor]) -> u32 {
+    contributors.iter().map(|contributor| contributor.contributions).sum()
 }
 
-/// Utility functions
+async fn acquire(concurrency: &Option<Arc<AdaptiveConcurrency>>) -> Option<Permit> {
+    match concurrency {
+        Some(concurrency) => Some(concurrency.acquire().await),
+        None => None,
+    }
+}
 
 fn take_first_n<T>(v: Vec<T>, n: u32) -> Vec<T> {
     v.into_iter().take(n as usize).collect()
 }
 
-//TODO async only for sake of StreamExt::filter_map
-async fn map_bus_factor_result(bus_factor: Result<Option<BusFactor>, JoinError>) -> Option<BusFactor> {
-    match bus_factor {
-        Ok(bus_factor) => bus_factor,
-        err => {
-            error!("Failed to calculate bus factor: {:?}", err);
-            None
+/// Numbers repositories in search order. Failed repositories are counted too, so ranks do not depend on failures.
+fn ranked<REPO>(rank: &mut u32, repo: Result<REPO, Failure>) -> Result<(u32, REPO), Failure> {
+    match repo {
+        Ok(repo) => {
+            *rank += 1;
+            Ok((*rank, repo))
+        }
+        Err(failure) => {
+            *rank += failure.failed_repos();
+            Err(failure)
         }
     }
 }
 
+/// Ends `bus_factors` with `Failure::DeadlineExceeded` after `deadline`. Dropping the stream aborts its tasks.
+fn with_deadline(bus_factors: BusFactorStream, deadline: Duration) -> BusFactorStream {
+    let deadline_at = Instant::now() + deadline;
+    stream::unfold(Some(bus_factors), move |bus_factors| async move {
+        let mut bus_factors = bus_factors?;
+        match tokio::time::timeout_at(deadline_at, bus_factors.next()).await {
+            Ok(Some(result)) => Some((result, Some(bus_factors))),
+            Ok(None) => None,
+            Err(_) => Some((Err(Failure::DeadlineExceeded(deadline)), None)),
+        }
+    })
+    .boxed()
+}
+
+fn map_bus_factor_result(bus_factor: Result<BusFactorResult, JoinError>) -> BusFactorResult {
+    bus_factor.unwrap_or_else(|err| Err(Failure::TaskPanicked(err.to_string())))
+}
+
 #[derive(Constructor)]
 struct Page {
     page_no: u32,
@@ -196,14 +668,17 @@ impl Paginator {
             }
             _ => {
                 self.page_no.add_assign(1);
-                self.remaining = self.remaining - self.max_page_size;
+                self.remaining -= self.max_page_size;
                 Some(Page::new(page_no, self.max_page_size))
             }
         }
     }
 }
 
-/// Tests
+// Tests
+
+#[cfg(test)]
+use crate::metric::{TopShare, TruckFactor};
 
 #[test]
 fn bus_factor_some_test() {
@@ -213,8 +688,8 @@ fn bus_factor_some_test() {
         Contributor::new("c", 1),
     ];
     let repo = "repo".to_string();
-    let bus_factor = contributors_bus_factor(contributors, repo.clone(), 0.6);
-    assert_eq!(bus_factor, Some(BusFactor::new(repo, "a".to_string(), 0.7)));
+    let bus_factor = contributors_bus_factor(&contributors, repo.clone(), &TopShare { threshold: 0.6 });
+    assert_eq!(bus_factor, Some(BusFactor::new(repo, 1, vec!["a".to_string()], 0.7)));
 }
 
 #[test]
@@ -225,14 +700,454 @@ fn bus_factor_none_test() {
         Contributor::new("c", 1),
     ];
     let repo = "repo".to_string();
-    let bus_factor = contributors_bus_factor(contributors, repo.clone(), 0.8);
-    assert_eq!(bus_factor, None);
+    let bus_factor = contributors_bus_factor(&contributors, repo.clone(), &TopShare { threshold: 0.8 });
+    assert_eq!(
+        bus_factor,
+        Some(BusFactor::new(repo, 1, vec!["a".to_string()], 0.7).with_reached(false))
+    );
 }
 
 #[test]
 fn bus_factor_onedev_test() {
     let contributors = vec![Contributor::new("a", 7)];
     let repo = "repo".to_string();
-    let bus_factor = contributors_bus_factor(contributors, repo.clone(), 0.99);
-    assert_eq!(bus_factor, Some(BusFactor::new(repo, "a".to_string(), 1.0)));
+    let bus_factor = contributors_bus_factor(&contributors, repo.clone(), &TopShare { threshold: 0.99 });
+    assert_eq!(bus_factor, Some(BusFactor::new(repo, 1, vec!["a".to_string()], 1.0)));
+}
+
+#[test]
+fn bus_factor_two_test() {
+    let contributors = vec![
+        Contributor::new("a", 5),
+        Contributor::new("b", 4),
+        Contributor::new("c", 1),
+    ];
+    let repo = "repo".to_string();
+    let metric = TruckFactor {
+        threshold: 0.8,
+        max_factor: 3,
+    };
+    let bus_factor = contributors_bus_factor(&contributors, repo.clone(), &metric);
+    assert_eq!(
+        bus_factor,
+        Some(BusFactor::new(repo, 2, vec!["a".to_string(), "b".to_string()], 0.9))
+    );
+}
+
+#[test]
+fn bus_factor_above_max_test() {
+    let contributors = vec![
+        Contributor::new("a", 4),
+        Contributor::new("b", 3),
+        Contributor::new("c", 3),
+    ];
+    let repo = "repo".to_string();
+    let metric = TruckFactor {
+        threshold: 0.9,
+        max_factor: 2,
+    };
+    let bus_factor = contributors_bus_factor(&contributors, repo, &metric).unwrap();
+    assert!(!bus_factor.reached);
+    assert_eq!(bus_factor.factor, 3);
+}
+
+#[test]
+fn bus_factor_no_contributions_test() {
+    let contributors = vec![Contributor::new("a", 0)];
+    let bus_factor = contributors_bus_factor(&contributors, "repo".to_string(), &TopShare { threshold: 0.5 });
+    assert_eq!(bus_factor, None);
+}
+
+#[test]
+fn contributors_window_from_str_test() {
+    assert_eq!("all".parse(), Ok(ContributorsWindow::All));
+    assert_eq!("90%".parse(), Ok(ContributorsWindow::Saturation(0.9)));
+    assert_eq!("30".parse(), Ok(ContributorsWindow::Top(30)));
+    assert!("0".parse::<ContributorsWindow>().is_err());
+    assert!("101%".parse::<ContributorsWindow>().is_err());
+    assert!("some".parse::<ContributorsWindow>().is_err());
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct TestRepo;
+
+#[cfg(test)]
+impl Repo for TestRepo {
+    type T = String;
+    fn name(&self) -> Self::T {
+        "repo".to_string()
+    }
+}
+
+/// Client serving contributors with given contributions, counting contributors requests.
+#[cfg(test)]
+struct TestClient {
+    contributions: Vec<u32>,
+    requests: std::sync::atomic::AtomicU32,
+    /// Number of listed contributors, after which pages are truncated
+    max_listed: Option<u32>,
+    /// Latency of contributors requests
+    delay: Duration,
+    /// Wait before contributors requests following the first one, e.g. for rate limit
+    pacing: Duration,
+}
+
+#[cfg(test)]
+impl TestClient {
+    fn new(contributions: Vec<u32>) -> Self {
+        let requests = std::sync::atomic::AtomicU32::new(0);
+        TestClient {
+            contributions,
+            requests,
+            max_listed: None,
+            delay: Duration::ZERO,
+            pacing: Duration::ZERO,
+        }
+    }
+
+    fn requests(&self) -> u32 {
+        self.requests.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Client<TestRepo, 10, 10, 1> for TestClient {
+    async fn top_repos(&self, _: String, _: u32, _: u32, _: Sort) -> crate::api::Result<Vec<TestRepo>> {
+        Ok(vec![TestRepo])
+    }
+
+    async fn top_contributors(
+        &self,
+        _: &TestRepo,
+        page: u32,
+        per_page: u32,
+    ) -> crate::api::Result<crate::api::ContributorsPage> {
+        if self.requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst) > 0 {
+            crate::wait::sleep(self.pacing).await;
+        }
+        tokio::time::sleep(self.delay).await;
+        let skip = (page - 1) * per_page;
+        let max_listed = self.max_listed.unwrap_or(u32::MAX);
+        let contributors = self
+            .contributions
+            .iter()
+            .enumerate()
+            .skip(skip as usize)
+            .take(std::cmp::min(per_page, max_listed.saturating_sub(skip)) as usize)
+            .map(|(i, contributions)| Contributor::new(format!("c{}", i), *contributions))
+            .collect();
+        let truncated = skip + per_page >= max_listed && (max_listed as usize) < self.contributions.len();
+        Ok(crate::api::ContributorsPage {
+            contributors,
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+type TestCalculator = BusFactorCalculator<TestRepo, 10, 10, 1, TestClient>;
+
+#[cfg(test)]
+fn test_settings(window: ContributorsWindow, early_exit: bool) -> ContributorsSettings {
+    ContributorsSettings {
+        metric: Arc::new(crate::metric::TopShare { threshold: 0.5 }),
+        window,
+        early_exit,
+        filter: ContributorFilter::default(),
+        aliases: ContributorAliases::default(),
+        concurrency: None,
+    }
+}
+
+#[tokio::test]
+async fn top_contributors_window_test() -> crate::api::Result<()> {
+    let contributions = (1..=25).rev().collect::<Vec<u32>>();
+
+    let client = TestClient::new(contributions.clone());
+    let contributors =
+        TestCalculator::top_contributors(&client, &TestRepo, &test_settings(ContributorsWindow::Top(15), false))
+            .await?
+            .contributors;
+    assert_eq!(contributors.len(), 15);
+    assert_eq!(client.requests(), 2);
+
+    let client = TestClient::new(contributions.clone());
+    let contributors =
+        TestCalculator::top_contributors(&client, &TestRepo, &test_settings(ContributorsWindow::All, false))
+            .await?
+            .contributors;
+    assert_eq!(contributors.len(), 25);
+    assert_eq!(client.requests(), 3);
+
+    // First page covers 205 of 310 contributions of first two pages
+    let client = TestClient::new(contributions);
+    let contributors = TestCalculator::top_contributors(
+        &client,
+        &TestRepo,
+        &test_settings(ContributorsWindow::Saturation(0.6), true),
+    )
+    .await?
+    .contributors;
+    assert_eq!(contributors.len(), 20);
+    assert_eq!(client.requests(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn top_contributors_early_exit_test() -> crate::api::Result<()> {
+    let metric = TopShare { threshold: 0.5 };
+    let contributions = (1..=25).rev().collect::<Vec<u32>>();
+
+    let client = TestClient::new(contributions.clone());
+    let settings = test_settings(ContributorsWindow::All, true);
+    let contributors = TestCalculator::top_contributors(&client, &TestRepo, &settings)
+        .await?
+        .contributors;
+    assert_eq!(client.requests(), 1, "Top share of first page is below threshold");
+    assert!(contributors_bus_factor(&contributors, "repo".to_string(), &metric).is_some_and(|bf| !bf.reached));
+
+    // Top contributor share bounds are 0.67 .. 0.92 after first page, 0.88 .. 0.91 after second, 0.91 after third
+    let contributions = [vec![1000], vec![10; 9], vec![1; 10], vec![0; 30]].concat();
+    let client = TestClient::new(contributions.clone());
+    let settings = test_settings(ContributorsWindow::Top(50), true);
+    let contributors = TestCalculator::top_contributors(&client, &TestRepo, &settings)
+        .await?
+        .contributors;
+    assert_eq!(client.requests(), 3);
+    let client = TestClient::new(contributions);
+    let settings = test_settings(ContributorsWindow::Top(50), false);
+    let all_contributors = TestCalculator::top_contributors(&client, &TestRepo, &settings)
+        .await?
+        .contributors;
+    assert_eq!(client.requests(), 5);
+    assert_eq!(
+        contributors_bus_factor(&contributors, "repo".to_string(), &metric),
+        contributors_bus_factor(&all_contributors, "repo".to_string(), &metric),
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn top_contributors_filter_test() -> crate::api::Result<()> {
+    // Contributors `c0` and `c2` are excluded, so the window is filled from the second page
+    let client = TestClient::new((1..=12).rev().collect());
+    let mut settings = test_settings(ContributorsWindow::Top(10), false);
+    settings.filter = ContributorFilter::none().try_with_patterns(["^c[02]$"])?;
+    let fetched = TestCalculator::top_contributors(&client, &TestRepo, &settings).await?;
+    assert_eq!(fetched.contributors.len(), 10);
+    assert_eq!(fetched.contributors.first().map(|c| c.name.as_str()), Some("c1"));
+    assert_eq!(fetched.excluded, vec!["c0", "c2"]);
+    assert_eq!(client.requests(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn top_contributors_aliases_test() -> crate::api::Result<()> {
+    // `c10` on the second page is an alias of `c1`, so it becomes top contributor
+    let client = TestClient::new((1..=12).rev().collect());
+    let mut settings = test_settings(ContributorsWindow::All, true);
+    settings.aliases = ContributorAliases::from_mailmap("<c1> <c10>")?;
+    let contributors = TestCalculator::top_contributors(&client, &TestRepo, &settings)
+        .await?
+        .contributors;
+    assert_eq!(contributors.len(), 11);
+    assert_eq!(
+        contributors.first().map(|c| (c.name.as_str(), c.contributions)),
+        Some(("c1", 13))
+    );
+    assert_eq!(client.requests(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn calculate_full_report_test() {
+    let calculate = |full_report| {
+        TestCalculator::new(TestClient::new(vec![5, 5]), TopShare { threshold: 0.6 })
+            .with_full_report(full_report)
+            .calculate("rust".to_string(), 1, 1, 1, Sort::Stars)
+            .map(Result::unwrap)
+            .collect::<Vec<BusFactor>>()
+    };
+    assert_eq!(calculate(false).await, Vec::new());
+    assert_eq!(
+        calculate(true).await,
+        vec![BusFactor::new("repo".to_string(), 1, vec!["c0".to_string()], 0.5)
+            .with_reached(false)
+            .with_rank(1)
+            .with_contributors_stats(&[Contributor::new("c0", 5), Contributor::new("c1", 5)])]
+    );
+}
+
+#[tokio::test]
+async fn calculate_full_report_early_exit_test() {
+    // Top share of first page is below threshold, which settles the result early
+    let contributions = (1..=25).rev().collect::<Vec<u32>>();
+    let all_contributors = contributions
+        .iter()
+        .enumerate()
+        .map(|(i, contributions)| Contributor::new(format!("c{}", i), *contributions))
+        .collect::<Vec<_>>();
+    let results = TestCalculator::new(TestClient::new(contributions), TopShare { threshold: 0.5 })
+        .with_contributors_window(ContributorsWindow::All)
+        .with_early_exit(true)
+        .with_full_report(true)
+        .calculate("rust".to_string(), 1, 1, 1, Sort::Stars)
+        .map(Result::unwrap)
+        .collect::<Vec<BusFactor>>()
+        .await;
+    let expected = contributors_bus_factor(&all_contributors, "repo".to_string(), &TopShare { threshold: 0.5 })
+        .map(|bus_factor| bus_factor.with_rank(1).with_contributors_stats(&all_contributors));
+    assert_eq!(results.into_iter().next(), expected);
+}
+
+#[tokio::test]
+async fn calculate_failure_test() {
+    let results = TestCalculator::new(TestClient::new(Vec::new()), TopShare { threshold: 0.6 })
+        .calculate("rust".to_string(), 1, 1, 1, Sort::Stars)
+        .collect::<Vec<BusFactorResult>>()
+        .await;
+    assert!(matches!(results.as_slice(), [Err(Failure::RepoEmpty { repo })] if repo == "repo"));
+    assert_eq!(results[0].as_ref().unwrap_err().as_ref(), "repo-empty");
+}
+
+#[tokio::test]
+async fn calculate_all_excluded_test() -> crate::api::Result<()> {
+    let calculator = TestCalculator::new(TestClient::new(vec![5, 1]), TopShare { threshold: 0.6 })
+        .with_contributor_filter(ContributorFilter::none().try_with_patterns(["^c[0-9]+$"])?)
+        .with_full_report(true);
+    let stats = calculator.stats();
+    let results = calculator
+        .calculate("rust".to_string(), 1, 1, 1, Sort::Stars)
+        .collect::<Vec<BusFactorResult>>()
+        .await;
+    assert!(matches!(
+        results.as_slice(),
+        [Ok(BusFactor { factor: 0, reached: false, excluded, .. })] if excluded == &["c0", "c1"]
+    ));
+    let summary = stats.summary();
+    assert_eq!(summary.repos_analyzed, 1);
+    assert_eq!(summary.repos_failed, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn top_contributors_truncated_test() -> crate::api::Result<()> {
+    let mut client = TestClient::new((1..=25).rev().collect());
+    client.max_listed = Some(10);
+    let fetched =
+        TestCalculator::top_contributors(&client, &TestRepo, &test_settings(ContributorsWindow::All, false)).await?;
+    assert_eq!(fetched.contributors.len(), 10);
+    assert!(fetched.truncated);
+    assert_eq!(client.requests(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn adaptive_concurrency_test() -> crate::api::Result<()> {
+    let client = TestClient::new((1..=25).rev().collect());
+    let concurrency = Arc::new(AdaptiveConcurrency::new(1, 4));
+    let settings = ContributorsSettings {
+        concurrency: Some(concurrency.clone()),
+        ..test_settings(ContributorsWindow::All, false)
+    };
+    TestCalculator::top_contributors(&client, &TestRepo, &settings).await?;
+    assert_eq!(client.requests(), 3);
+    assert_eq!(
+        concurrency.limit(),
+        3,
+        "Successful requests should increase concurrency"
+    );
+
+    let results = TestCalculator::new(TestClient::new(vec![5, 1]), TopShare { threshold: 0.6 })
+        .with_adaptive_concurrency(true)
+        .calculate("rust".to_string(), 25, 2, 4, Sort::Stars)
+        .collect::<Vec<BusFactorResult>>()
+        .await;
+    assert_eq!(results.len(), 3, "Every page of repositories should be analyzed");
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn adaptive_concurrency_pacing_test() -> crate::api::Result<()> {
+    let mut client = TestClient::new((1..=25).rev().collect());
+    client.delay = Duration::from_millis(10);
+    client.pacing = Duration::from_secs(1);
+    let concurrency = Arc::new(AdaptiveConcurrency::new(1, 4));
+    let settings = ContributorsSettings {
+        concurrency: Some(concurrency.clone()),
+        ..test_settings(ContributorsWindow::All, false)
+    };
+    TestCalculator::top_contributors(&client, &TestRepo, &settings).await?;
+    assert_eq!(
+        concurrency.limit(),
+        3,
+        "Waits of client before requests should not count as their latency"
+    );
+    Ok(())
+}
+
+#[test]
+fn ranked_test() {
+    let mut rank = 0;
+    assert!(matches!(ranked(&mut rank, Ok("a")), Ok((1, "a"))));
+    let failure = Failure::SearchPageFailed {
+        page: 2,
+        repos: 10,
+        error: Error::Error("error".to_string()),
+    };
+    assert!(ranked::<&str>(&mut rank, Err(failure)).is_err());
+    assert!(matches!(ranked(&mut rank, Ok("b")), Ok((12, "b"))));
+}
+
+#[tokio::test]
+async fn calculate_unordered_test() {
+    let mut results = TestCalculator::new(TestClient::new(vec![5, 1]), TopShare { threshold: 0.6 })
+        .with_unordered(true)
+        .calculate("rust".to_string(), 25, 3, 3, Sort::Stars)
+        .map(Result::unwrap)
+        .collect::<Vec<BusFactor>>()
+        .await;
+    results.sort_by_key(|bus_factor| bus_factor.rank);
+    let ranks = results.iter().map(|bus_factor| bus_factor.rank).collect::<Vec<_>>();
+    assert_eq!(ranks, vec![1, 2, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn calculate_deadline_test() {
+    let mut client = TestClient::new(vec![5, 1]);
+    client.delay = Duration::from_secs(1);
+    let results = TestCalculator::new(client, TopShare { threshold: 0.6 })
+        .with_deadline(Duration::from_millis(1500))
+        .calculate("rust".to_string(), 25, 1, 1, Sort::Stars)
+        .collect::<Vec<BusFactorResult>>()
+        .await;
+    assert!(matches!(results.as_slice(), [Ok(_), Err(Failure::DeadlineExceeded(_))]));
+
+    let results = TestCalculator::new(TestClient::new(vec![5, 1]), TopShare { threshold: 0.6 })
+        .with_deadline(Duration::from_secs(60))
+        .calculate("rust".to_string(), 25, 1, 1, Sort::Stars)
+        .collect::<Vec<BusFactorResult>>()
+        .await;
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(Result::is_ok));
+}
+
+#[tokio::test]
+async fn run_stats_test() {
+    let calculator = TestCalculator::new(TestClient::new(vec![5, 5]), TopShare { threshold: 0.6 });
+    let stats = calculator.stats();
+    let results = calculator
+        .calculate("rust".to_string(), 25, 1, 1, Sort::Stars)
+        .collect::<Vec<BusFactorResult>>()
+        .await;
+    assert!(results.is_empty());
+    let summary = stats.summary();
+    assert_eq!(summary.repos_requested, 25);
+    assert_eq!(summary.repos_searched, 3);
+    assert_eq!(summary.repos_analyzed, 3);
+    assert_eq!(summary.repos_reported, 0);
+    assert_eq!(summary.repos_failed, 0);
+    assert_eq!(summary.repos_skipped, 22);
 }
@@ -0,0 +1,56 @@
+use chrono::Utc;
+use std::fmt::Debug;
+
+/// Source of current time used by rate limiters.
+pub trait Clock: Debug + Send + Sync {
+    /// Current Unix time in milliseconds.
+    fn now_millis(&self) -> i64;
+
+    /// Current Unix time in seconds.
+    fn now(&self) -> i64 {
+        self.now_millis().div_euclid(1000)
+    }
+}
+
+/// System time clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        Utc::now().timestamp_millis()
+    }
+}
+
+/// Clock starting at given time and advancing with Tokio time,
+/// so with `tokio::time::pause` it advances only by (auto-advanced) sleeps.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedClock {
+    start_millis: i64,
+    start: tokio::time::Instant,
+}
+
+impl SimulatedClock {
+    pub fn new(start_millis: i64) -> Self {
+        SimulatedClock {
+            start_millis,
+            start: tokio::time::Instant::now(),
+        }
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_millis(&self) -> i64 {
+        self.start_millis + self.start.elapsed().as_millis() as i64
+    }
+}
+
+// Tests
+
+#[tokio::test(start_paused = true)]
+async fn simulated_clock_test() {
+    let clock = SimulatedClock::new(1_000_000);
+    assert_eq!(clock.now(), 1000);
+    tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+    assert_eq!(clock.now_millis(), 1_000_000 + 3_600_000);
+}
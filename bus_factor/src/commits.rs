@@ -0,0 +1,85 @@
+use crate::api::{Contributor, ContributorsPage};
+use std::collections::HashMap;
+
+/// Numbers of commits by author, counted from history by clients of services without contributors endpoint.
+#[derive(Debug, Default)]
+pub struct CommitCounts {
+    authors: HashMap<String, u32>,
+    commits: u32,
+}
+
+impl CommitCounts {
+    /// Counts commit, and its author if known.
+    pub fn add(&mut self, author: Option<String>) {
+        self.commits += 1;
+        if let Some(author) = author {
+            *self.authors.entry(author).or_default() += 1;
+        }
+    }
+
+    /// Number of counted commits, including commits of unknown authors.
+    pub fn commits(&self) -> u32 {
+        self.commits
+    }
+
+    /// Authors sorted by their commits. `truncated` if only part of history has been counted.
+    pub fn into_authors(self, truncated: bool) -> AuthorCommits {
+        let mut authors = self.authors.into_iter().collect::<Vec<_>>();
+        authors.sort_by(|(name, commits), (other_name, other_commits)| {
+            other_commits.cmp(commits).then_with(|| name.cmp(other_name))
+        });
+        AuthorCommits { authors, truncated }
+    }
+}
+
+/// Numbers of commits by author, sorted descending.
+#[derive(Debug, Default)]
+pub struct AuthorCommits {
+    authors: Vec<(String, u32)>,
+    truncated: bool,
+}
+
+impl AuthorCommits {
+    /// Page of authors as contributors, with first page number 1. Authors named `...[bot]` are bots.
+    /// Page is truncated when it reaches the end of counted part of history.
+    pub fn page(&self, page: u32, per_page: u32) -> ContributorsPage {
+        let skip = (page.saturating_sub(1) * per_page) as usize;
+        let contributors = self
+            .authors
+            .iter()
+            .skip(skip)
+            .take(per_page as usize)
+            .map(|(name, commits)| Contributor {
+                name: name.clone(),
+                contributions: *commits,
+                bot: name.ends_with("[bot]"),
+            })
+            .collect();
+        ContributorsPage {
+            contributors,
+            truncated: self.truncated && skip + per_page as usize >= self.authors.len(),
+        }
+    }
+}
+
+// Tests
+
+#[test]
+fn author_commits_page_test() {
+    let mut counts = CommitCounts::default();
+    for author in ["b", "a", "a", "renovate[bot]", "b", "a"] {
+        counts.add(Some(author.to_string()));
+    }
+    counts.add(None);
+    assert_eq!(counts.commits(), 7);
+    let authors = counts.into_authors(true);
+    let page = authors.page(1, 2);
+    assert_eq!(page.contributors.len(), 2);
+    assert_eq!(page.contributors[0].name, "a");
+    assert_eq!(page.contributors[0].contributions, 3);
+    assert!(!page.truncated);
+    let page = authors.page(2, 2);
+    assert_eq!(page.contributors.len(), 1);
+    assert!(page.contributors[0].bot);
+    assert!(page.truncated, "Last page of truncated history should be truncated");
+}
@@ -0,0 +1,213 @@
+use crate::api::Error;
+use log::debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Requests slower than this multiple of the baseline latency are a sign of overload.
+const LATENCY_TOLERANCE: f64 = 2.0;
+
+/// Latency increase (in seconds) below which requests are not considered slow, whatever their baseline.
+const MIN_LATENCY_INCREASE: f64 = 0.05;
+
+/// Baseline latency drift towards current latency, so it follows permanent changes of service speed.
+const BASELINE_DRIFT: f64 = 0.001;
+
+/// Ratio of remaining rate limit below which concurrency is lowered.
+const LOW_RATE_LIMIT: f32 = 0.1;
+
+/// Ratio by which concurrency is multiplied on overload.
+const DECREASE_RATIO: f64 = 0.5;
+
+/// Concurrency limit adjusted with AIMD (additive increase, multiplicative decrease).
+/// Limit grows by one after `limit` successful fast requests,
+/// and is halved on overload signal: failure, latency spike, or low remaining rate limit.
+#[derive(Debug)]
+pub struct AdaptiveConcurrency {
+    state: Mutex<State>,
+    released: Notify,
+}
+
+#[derive(Debug)]
+struct State {
+    limit: usize,
+    min: usize,
+    max: usize,
+    in_flight: usize,
+    /// Successful requests since last change of limit
+    successes: usize,
+    /// Requests since last decrease. Overload signals are ignored until `limit` requests completed,
+    /// because requests in flight were sent before decrease.
+    since_decrease: usize,
+    /// Latency of fast requests, in seconds
+    baseline: Option<f64>,
+}
+
+/// Outcome of a request observed by `AdaptiveConcurrency`.
+pub struct Observation<'a> {
+    pub latency: Duration,
+    pub error: Option<&'a Error>,
+    /// Ratio of remaining requests to rate limit, if known
+    pub remaining_rate_limit: Option<f32>,
+}
+
+/// Slot of concurrency limit, released on drop.
+pub struct Permit {
+    concurrency: Arc<AdaptiveConcurrency>,
+}
+
+impl AdaptiveConcurrency {
+    /// Concurrency starting at half of `max`, never lower than `min`.
+    pub fn new(min: usize, max: usize) -> Self {
+        let min = min.clamp(1, max.max(1));
+        let max = max.max(min);
+        AdaptiveConcurrency {
+            state: Mutex::new(State {
+                limit: (max / 2).max(min),
+                min,
+                max,
+                in_flight: 0,
+                successes: 0,
+                since_decrease: usize::MAX,
+                baseline: None,
+            }),
+            released: Notify::new(),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+
+    /// Waits until number of permits in use is below the limit.
+    pub async fn acquire(self: &Arc<Self>) -> Permit {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    return Permit {
+                        concurrency: self.clone(),
+                    };
+                }
+            }
+            self.released.notified().await;
+        }
+    }
+
+    pub fn observe(&self, observation: Observation) {
+        let mut state = self.state.lock().unwrap();
+        state.since_decrease = state.since_decrease.saturating_add(1);
+        let latency = observation.latency.as_secs_f64();
+        let baseline = state.baseline.map_or(latency, |baseline| {
+            latency.min(baseline + (latency - baseline) * BASELINE_DRIFT)
+        });
+        state.baseline = Some(baseline);
+        let overloaded = observation.error.is_some_and(is_overload)
+            || (latency > baseline * LATENCY_TOLERANCE && latency - baseline > MIN_LATENCY_INCREASE)
+            || observation
+                .remaining_rate_limit
+                .is_some_and(|remaining| remaining < LOW_RATE_LIMIT);
+        if overloaded {
+            if state.since_decrease >= state.limit {
+                state.limit = ((state.limit as f64 * DECREASE_RATIO) as usize).max(state.min);
+                state.successes = 0;
+                state.since_decrease = 0;
+                debug!("Concurrency decreased to {}", state.limit);
+            }
+        } else if observation.error.is_none() {
+            state.successes += 1;
+            if state.successes >= state.limit && state.limit < state.max {
+                state.limit += 1;
+                state.successes = 0;
+                debug!("Concurrency increased to {}", state.limit);
+                self.released.notify_one();
+            }
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.concurrency.state.lock().unwrap().in_flight -= 1;
+        self.concurrency.released.notify_one();
+    }
+}
+
+/// Errors caused by too many requests, or by service being unable to handle them.
+fn is_overload(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::RateLimited { .. } | Error::ServerError(_) | Error::Timeout(_) | Error::Transport(_)
+    )
+}
+
+// Tests
+
+#[cfg(test)]
+fn observation(millis: u64, error: Option<&Error>) -> Observation<'_> {
+    Observation {
+        latency: Duration::from_millis(millis),
+        error,
+        remaining_rate_limit: None,
+    }
+}
+
+#[test]
+fn increase_test() {
+    let concurrency = AdaptiveConcurrency::new(1, 4);
+    assert_eq!(concurrency.limit(), 2);
+    concurrency.observe(observation(100, None));
+    assert_eq!(concurrency.limit(), 2);
+    concurrency.observe(observation(100, None));
+    assert_eq!(concurrency.limit(), 3);
+    for _ in 0..10 {
+        concurrency.observe(observation(100, None));
+    }
+    assert_eq!(concurrency.limit(), 4, "Limit should not exceed max");
+}
+
+#[test]
+fn decrease_test() {
+    let concurrency = AdaptiveConcurrency::new(1, 8);
+    assert_eq!(concurrency.limit(), 4);
+    let error = Error::ServerError(Default::default());
+    concurrency.observe(observation(100, Some(&error)));
+    assert_eq!(concurrency.limit(), 2);
+    concurrency.observe(observation(100, Some(&error)));
+    assert_eq!(
+        concurrency.limit(),
+        2,
+        "Requests in flight before decrease should not decrease it again"
+    );
+    concurrency.observe(observation(100, Some(&error)));
+    assert_eq!(concurrency.limit(), 1);
+
+    let concurrency = AdaptiveConcurrency::new(1, 8);
+    concurrency.observe(observation(100, None));
+    concurrency.observe(observation(1000, None));
+    assert_eq!(concurrency.limit(), 2, "Latency spike should decrease limit");
+
+    let concurrency = AdaptiveConcurrency::new(1, 8);
+    concurrency.observe(Observation {
+        remaining_rate_limit: Some(0.01),
+        ..observation(100, None)
+    });
+    assert_eq!(concurrency.limit(), 2, "Low remaining rate limit should decrease limit");
+
+    let concurrency = AdaptiveConcurrency::new(1, 8);
+    let error = Error::NotFound(Default::default());
+    concurrency.observe(observation(100, Some(&error)));
+    assert_eq!(concurrency.limit(), 4, "Not found repository is not a sign of overload");
+}
+
+#[tokio::test]
+async fn acquire_test() {
+    let concurrency = Arc::new(AdaptiveConcurrency::new(1, 2));
+    let permit = concurrency.acquire().await;
+    let waiting = tokio::time::timeout(Duration::from_millis(10), concurrency.acquire()).await;
+    assert!(waiting.is_err(), "Second permit should wait for the first one");
+    drop(permit);
+    let acquired = tokio::time::timeout(Duration::from_secs(1), concurrency.acquire()).await;
+    assert!(acquired.is_ok(), "Second permit should be acquired after release");
+}
@@ -0,0 +1,108 @@
+use crate::api::{Contributor, Error, Result};
+use regex::Regex;
+
+/// Logins of well known bot and automation accounts.
+pub const DEFAULT_DENY_LIST: &[&str] = &[
+    "dependabot[bot]",
+    "dependabot-preview[bot]",
+    "github-actions[bot]",
+    "renovate[bot]",
+    "renovate-bot",
+    "greenkeeper[bot]",
+    "pre-commit-ci[bot]",
+    "codecov[bot]",
+    "allcontributors[bot]",
+    "imgbot[bot]",
+    "mergify[bot]",
+    "snyk-bot",
+    "bors",
+    "bors[bot]",
+];
+
+/// Excludes bot and automation accounts from contributors before they are measured.
+#[derive(Debug, Clone)]
+pub struct ContributorFilter {
+    deny_list: Vec<String>,
+    exclude_bots: bool,
+    patterns: Vec<Regex>,
+}
+
+/// Excludes accounts from `DEFAULT_DENY_LIST` and accounts of `Bot` type.
+impl Default for ContributorFilter {
+    fn default() -> Self {
+        ContributorFilter {
+            deny_list: DEFAULT_DENY_LIST.iter().map(|login| login.to_string()).collect(),
+            exclude_bots: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl ContributorFilter {
+    /// Filter excluding no one.
+    pub fn none() -> Self {
+        ContributorFilter {
+            deny_list: Vec::new(),
+            exclude_bots: false,
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Additionally excludes contributors with names matching any of `patterns` regexes.
+    pub fn try_with_patterns<STR: AsRef<str>>(mut self, patterns: impl IntoIterator<Item = STR>) -> Result<Self> {
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let regex = Regex::new(pattern)
+                .map_err(|err| Error::Error(format!("Invalid exclude pattern {}: {}", pattern, err)))?;
+            self.patterns.push(regex);
+        }
+        Ok(self)
+    }
+
+    pub fn is_excluded(&self, contributor: &Contributor) -> bool {
+        (self.exclude_bots && contributor.bot)
+            || self
+                .deny_list
+                .iter()
+                .any(|login| login.eq_ignore_ascii_case(&contributor.name))
+            || self.patterns.iter().any(|regex| regex.is_match(&contributor.name))
+    }
+
+    /// Splits `contributors` into included and excluded ones, preserving their order.
+    pub fn partition(&self, contributors: Vec<Contributor>) -> (Vec<Contributor>, Vec<Contributor>) {
+        contributors
+            .into_iter()
+            .partition(|contributor| !self.is_excluded(contributor))
+    }
+}
+
+// Tests
+
+#[test]
+fn default_filter_test() {
+    let filter = ContributorFilter::default();
+    assert!(filter.is_excluded(&Contributor::new("dependabot[bot]", 10)));
+    assert!(filter.is_excluded(&Contributor::new("Bors", 10)));
+    let mut bot = Contributor::new("some-app", 10);
+    bot.bot = true;
+    assert!(filter.is_excluded(&bot));
+    assert!(!filter.is_excluded(&Contributor::new("alice", 10)));
+    assert!(!ContributorFilter::none().is_excluded(&bot));
+}
+
+#[test]
+fn patterns_filter_test() -> Result<()> {
+    let filter = ContributorFilter::none().try_with_patterns(["-bot$", "^ci-"])?;
+    let contributors = vec![
+        Contributor::new("alice", 7),
+        Contributor::new("release-bot", 5),
+        Contributor::new("ci-runner", 3),
+        Contributor::new("bob", 1),
+    ];
+    let (included, excluded) = filter.partition(contributors);
+    let names = |contributors: Vec<Contributor>| contributors.into_iter().map(|c| c.name).collect::<Vec<_>>();
+    assert_eq!(names(included), vec!["alice", "bob"]);
+    assert_eq!(names(excluded), vec!["release-bot", "ci-runner"]);
+    assert!(ContributorFilter::none().try_with_patterns(["("]).is_err());
+    Ok(())
+}
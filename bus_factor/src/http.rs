@@ -0,0 +1,206 @@
+use crate::api::RequestContext;
+use crate::retry::{Retry, Retryable};
+use reqwest::header::HeaderMap;
+use reqwest::Response;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Pause after 429 response without `Retry-After` header.
+const DEFAULT_RATE_LIMITED_PAUSE: u64 = 60;
+
+/// Parser of error message from body of unsuccessful response. `None` if body is not an error payload of the service.
+pub type ErrorMessage = fn(&str) -> Option<String>;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error of HTTP clients of services without client specific errors.
+#[allow(clippy::enum_variant_names)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Error: {0}")]
+    Error(String),
+    #[error("Request error: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Status error: {0}")]
+    StatusError(#[from] StatusError),
+}
+
+/// Requests failed with transient error are retried with backoff, and rate limited ones are re-issued after advised pause.
+impl Retryable for Error {
+    fn retry(&self, idempotent: bool) -> Retry {
+        match self {
+            Error::StatusError(error) if error.status == StatusCode::TOO_MANY_REQUESTS => Retry::RateLimited(
+                Duration::from_secs(error.retry_after.unwrap_or(DEFAULT_RATE_LIMITED_PAUSE)),
+            ),
+            Error::StatusError(error) => retry_status(error.status, idempotent),
+            Error::RequestError(error) => retry_request_error(error, idempotent),
+            Error::Error(_) => Retry::Never,
+        }
+    }
+}
+
+/// Transient server errors are retried. Internal server error may occur after request has been processed,
+/// so it is retried only for `idempotent` requests.
+pub fn retry_status(status: StatusCode, idempotent: bool) -> Retry {
+    match status {
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => Retry::Backoff,
+        StatusCode::INTERNAL_SERVER_ERROR if idempotent => Retry::Backoff,
+        _ => Retry::Never,
+    }
+}
+
+/// Connection errors are retried. Timeouts and errors of sending request or reading response body may occur
+/// after request has been processed, so they are retried only for `idempotent` requests.
+pub fn retry_request_error(error: &reqwest::Error, idempotent: bool) -> Retry {
+    if error.is_connect() || (idempotent && (error.is_timeout() || error.is_request() || error.is_body())) {
+        Retry::Backoff
+    } else {
+        Retry::Never
+    }
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::Error(msg)
+    }
+}
+
+/// Unsuccessful response.
+#[derive(Error, Debug)]
+#[error("{status} {message} [{url}]")]
+pub struct StatusError {
+    pub status: StatusCode,
+    pub url: String,
+    /// Time of rate limit reset, from `RateLimit-Reset` header
+    pub reset: Option<i64>,
+    /// Seconds to wait before re-issuing request, from `Retry-After` header
+    pub retry_after: Option<u64>,
+    /// Message from response body, if any
+    pub message: String,
+}
+
+impl StatusError {
+    /// Reads error of unsuccessful response. Body, which is not an error payload, is taken as the message.
+    pub async fn read(response: Response, error_message: ErrorMessage) -> Self {
+        let status = response.status();
+        let url = response.url().to_string();
+        let reset = header_value::<i64>(response.headers(), "ratelimit-reset");
+        let retry_after = header_value::<u64>(response.headers(), "retry-after");
+        let body = response.text().await.unwrap_or_default();
+        let message = error_message(&body).unwrap_or(body);
+        StatusError {
+            status,
+            url,
+            reset,
+            retry_after,
+            message,
+        }
+    }
+}
+
+impl From<StatusError> for crate::api::Error {
+    fn from(err: StatusError) -> Self {
+        let context = RequestContext::new(Some(err.url), Some(err.status.as_u16()), err.message);
+        match err.status {
+            StatusCode::TOO_MANY_REQUESTS => crate::api::Error::RateLimited {
+                reset: err.reset,
+                context,
+            },
+            StatusCode::UNAUTHORIZED => crate::api::Error::Unauthorized(context),
+            StatusCode::FORBIDDEN => crate::api::Error::Forbidden(context),
+            StatusCode::NOT_FOUND => crate::api::Error::NotFound(context),
+            status if status.is_server_error() => crate::api::Error::ServerError(context),
+            _ => crate::api::Error::RequestError(context),
+        }
+    }
+}
+
+impl From<Error> for crate::api::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::StatusError(err) => err.into(),
+            Error::RequestError(err) => {
+                let url = err.url().map(|url| url.to_string());
+                let status = err.status().map(|status| status.as_u16());
+                let context = RequestContext::new(url, status, err.to_string());
+                if err.is_timeout() {
+                    crate::api::Error::Timeout(context)
+                } else if err.is_decode() {
+                    crate::api::Error::Decode(context)
+                } else if status.is_some() {
+                    crate::api::Error::RequestError(context)
+                } else {
+                    crate::api::Error::Transport(context)
+                }
+            }
+            Error::Error(message) => crate::api::Error::Error(message),
+        }
+    }
+}
+
+/// Payload of successful response, or `StatusError` of unsuccessful one.
+pub async fn read_response<PAYLOAD: DeserializeOwned>(
+    response: Response,
+    error_message: ErrorMessage,
+) -> Result<PAYLOAD> {
+    match response.status() {
+        status if status.is_client_error() || status.is_server_error() => {
+            Err(StatusError::read(response, error_message).await.into())
+        }
+        _ => Ok(response.json::<PAYLOAD>().await?),
+    }
+}
+
+pub fn header_value<T: FromStr>(headers: &HeaderMap, header: &str) -> Option<T> {
+    headers.get(header)?.to_str().ok()?.parse().ok()
+}
+
+// Tests
+
+#[cfg(test)]
+fn status_error(status: StatusCode) -> StatusError {
+    StatusError {
+        status,
+        url: "https://example.com/api/v1/repos".to_string(),
+        reset: Some(1000),
+        retry_after: None,
+        message: "message".to_string(),
+    }
+}
+
+#[test]
+fn status_error_test() {
+    use crate::api::Error as ApiError;
+    let rate_limited = ApiError::from(status_error(StatusCode::TOO_MANY_REQUESTS));
+    assert!(matches!(rate_limited, ApiError::RateLimited { reset: Some(1000), .. }));
+    assert_eq!(rate_limited.status(), Some(429));
+    assert!(matches!(
+        ApiError::from(status_error(StatusCode::UNAUTHORIZED)),
+        ApiError::Unauthorized(_)
+    ));
+    assert!(matches!(
+        ApiError::from(status_error(StatusCode::NOT_FOUND)),
+        ApiError::NotFound(_)
+    ));
+    assert!(matches!(
+        ApiError::from(status_error(StatusCode::BAD_GATEWAY)),
+        ApiError::ServerError(_)
+    ));
+}
+
+#[test]
+fn retry_test() {
+    let retry = |status| Error::StatusError(status_error(status)).retry(true);
+    assert_eq!(retry(StatusCode::BAD_GATEWAY), Retry::Backoff);
+    assert_eq!(retry(StatusCode::INTERNAL_SERVER_ERROR), Retry::Backoff);
+    assert_eq!(retry_status(StatusCode::INTERNAL_SERVER_ERROR, false), Retry::Never);
+    assert_eq!(retry(StatusCode::NOT_FOUND), Retry::Never);
+    assert_eq!(
+        retry(StatusCode::TOO_MANY_REQUESTS),
+        Retry::RateLimited(Duration::from_secs(DEFAULT_RATE_LIMITED_PAUSE))
+    );
+    assert_eq!(Error::Error("error".to_string()).retry(true), Retry::Never);
+}
@@ -6,11 +6,19 @@
 //! It is commonly used in the context of software development.
 //! For example, if a given repository is developed by a single person, then the repository's bus factor is equal to 1 (it's likely for the repository to become unmaintained if the main contributor suddenly stops working on it).
 //!
-//! Library finds popular GitHub repositories with a bus factor of 1.
+//! Library finds popular GitHub repositories with a low bus factor.
 //! Given a programming language name (`language`) and a repository count (`repo_count`), library fetches the first `repo_count` most popular repositories (sorted by the number of GitHub stars) from the given language.
 //! Then, for each repository, it inspect its contributor statistics.
-//! We assume a repository's bus factor is 1 if its most active developer's contributions account for 75% or more of the total contributions count from the top 25 most active developers.
-//! Repositories with a bus factor of 75% or higher are returned as a Result.
+//! Repository's bus factor is the smallest number of its most active developers whose combined contributions account for 75% (`threshold`) or more of the total contributions count from the top 25 (`contributors_window`) most active developers.
+//! Repositories with a bus factor not larger than `max_factor` (1 by default) are returned as a Result, together with their key developers.
+//! With full report every analyzed repository is returned, classified by whether it has reached the bus factor condition.
+//! Other contributions concentration metrics (top contributor share, Gini coefficient, Shannon entropy, Herfindahl-Hirschman index) can be used instead, by implementing `BusFactorMetric`.
+//! Bot and automation accounts (e.g. `dependabot[bot]`, `bors`) are excluded from contributors by `ContributorFilter`.
+//! Contributions of the same person made under several logins or emails are merged by `ContributorAliases`.
+//! With adaptive concurrency, number of parallel requests follows their latency, failures and remaining rate limit.
+
+#[cfg(feature = "calculator")]
+pub mod alias;
 
 #[cfg(feature = "api")]
 pub mod api;
@@ -18,9 +26,53 @@ pub mod api;
 #[cfg(feature = "calculator")]
 pub mod calculator;
 
+#[cfg(feature = "client")]
+pub mod clock;
+
+#[cfg(feature = "api")]
+pub mod commits;
+
+#[cfg(feature = "calculator")]
+pub mod concurrency;
+
+#[cfg(feature = "calculator")]
+pub mod filter;
+
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "calculator")]
+pub mod metric;
+
+#[cfg(feature = "client")]
+pub mod retry;
+
+#[cfg(feature = "calculator")]
+pub mod summary;
+
+#[cfg(feature = "client")]
+pub mod task;
+
+#[cfg(feature = "client")]
+pub mod wait;
+
+#[cfg(feature = "calculator")]
+pub use alias::ContributorAliases;
 #[cfg(feature = "calculator")]
 pub use calculator::BusFactor;
 #[cfg(feature = "calculator")]
 pub use calculator::BusFactorCalculator;
 #[cfg(feature = "calculator")]
+pub use calculator::BusFactorResult;
+#[cfg(feature = "calculator")]
 pub use calculator::BusFactorStream;
+#[cfg(feature = "calculator")]
+pub use calculator::Failure;
+#[cfg(feature = "calculator")]
+pub use concurrency::AdaptiveConcurrency;
+#[cfg(feature = "calculator")]
+pub use filter::ContributorFilter;
+#[cfg(feature = "calculator")]
+pub use metric::BusFactorMetric;
+#[cfg(feature = "calculator")]
+pub use summary::{RunStats, RunSummary};
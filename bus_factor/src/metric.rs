@@ -0,0 +1,353 @@
+use crate::api::Contributor;
+use strum_macros::{AsRefStr, EnumString};
+
+/// Result of measuring contributions concentration of a repository.
+#[derive(Debug, PartialEq)]
+pub struct Measurement {
+    /// Concentration score from range [0.0,1.0] rounded to two decimal points. Higher means more concentrated.
+    pub score: f32,
+    /// Key contributors sorted by contributions in desc order
+    pub contributors: Vec<String>,
+    /// Whether bus factor condition of the metric has been met
+    pub reached: bool,
+}
+
+/// Concentration metric of contributions used by `BusFactorCalculator`.
+pub trait BusFactorMetric: Send + Sync {
+    /// Returns `None` if there are no contributions to measure.
+    ///
+    /// # Arguments
+    /// * `contributors` - List of `Contributor`s sorted by contributions in desc order
+    fn measure(&self, contributors: &[Contributor]) -> Option<Measurement>;
+
+    /// Returns `true` if `measure` result can no longer change after fetching remaining contributors,
+    /// which have no more contributions than the last of `contributors`.
+    ///
+    /// # Arguments
+    /// * `contributors` - List of `Contributor`s fetched so far, sorted by contributions in desc order
+    /// * `remaining` - Maximal number of not fetched contributors, if known
+    fn is_settled(&self, _contributors: &[Contributor], _remaining: Option<u32>) -> bool {
+        false
+    }
+}
+
+/// Lower and upper bound of contributions ratio.
+#[derive(Debug, PartialEq)]
+pub struct ShareBounds {
+    pub lower: f32,
+    pub upper: f32,
+}
+
+impl ShareBounds {
+    /// Bounds of contributions ratio of `top` contributors to total contributions of all contributors.
+    /// Returns `None` if not all of `top` contributors have been fetched yet.
+    ///
+    /// # Arguments
+    /// * `contributors` - List of `Contributor`s fetched so far, sorted by contributions in desc order
+    /// * `remaining` - Maximal number of not fetched contributors, if known
+    pub fn of_top(contributors: &[Contributor], top: usize, remaining: Option<u32>) -> Option<ShareBounds> {
+        if contributors.len() < top && remaining != Some(0) {
+            return None;
+        }
+        let total = total_contributions(contributors)? as f64;
+        let top_contributions =
+            total_contributions(&contributors[..std::cmp::min(top, contributors.len())]).unwrap_or(0) as f64;
+        let lower = match remaining {
+            Some(remaining) => {
+                let last_contributions = contributors.last().map_or(0, |c| c.contributions);
+                top_contributions / (total + remaining as f64 * last_contributions as f64)
+            }
+            None => 0.0,
+        };
+        let upper = top_contributions / total;
+        Some(ShareBounds {
+            lower: lower as f32,
+            upper: upper as f32,
+        })
+    }
+
+    /// Upper bound rounded as measured score, so that it is compared with threshold the same way.
+    fn rounded_upper(&self) -> f32 {
+        round(self.upper as f64)
+    }
+
+    /// Bounds equal after rounding mean the measured contributions ratio is known.
+    fn is_exact(&self) -> bool {
+        round(self.lower as f64) == round(self.upper as f64)
+    }
+}
+
+#[derive(Debug, EnumString, Clone, Copy, AsRefStr)]
+pub enum Metric {
+    #[strum(serialize = "top-share")]
+    TopShare,
+    #[strum(serialize = "gini")]
+    Gini,
+    #[strum(serialize = "entropy")]
+    Entropy,
+    #[strum(serialize = "hhi")]
+    Herfindahl,
+    #[strum(serialize = "truck-factor")]
+    TruckFactor,
+}
+
+/// Contributions ratio of top(first) contributor.
+pub struct TopShare {
+    pub threshold: f32,
+}
+
+impl BusFactorMetric for TopShare {
+    fn measure(&self, contributors: &[Contributor]) -> Option<Measurement> {
+        let total = total_contributions(contributors)?;
+        let top_contributor = contributors.first()?;
+        let score = calculate_percentage(top_contributor.contributions, total);
+        Some(top_contributor_measurement(contributors, score, self.threshold))
+    }
+
+    fn is_settled(&self, contributors: &[Contributor], remaining: Option<u32>) -> bool {
+        ShareBounds::of_top(contributors, 1, remaining)
+            .is_some_and(|bounds| bounds.rounded_upper() < self.threshold || bounds.is_exact())
+    }
+}
+
+/// Smallest number of top contributors whose combined contributions ratio reaches `threshold`.
+/// Bus factor is reached if the number is not larger than `max_factor`.
+pub struct TruckFactor {
+    pub threshold: f32,
+    pub max_factor: u32,
+}
+
+impl BusFactorMetric for TruckFactor {
+    fn measure(&self, contributors: &[Contributor]) -> Option<Measurement> {
+        let total = total_contributions(contributors)?;
+        let mut key_contributions = 0;
+        let mut key_contributors = Vec::new();
+        let mut score = 0.0;
+        for contributor in contributors {
+            key_contributions += contributor.contributions;
+            key_contributors.push(contributor.name.clone());
+            score = calculate_percentage(key_contributions, total);
+            if score >= self.threshold {
+                break;
+            }
+        }
+        let reached = key_contributors.len() as u32 <= self.max_factor;
+        Some(Measurement {
+            score,
+            contributors: key_contributors,
+            reached,
+        })
+    }
+
+    /// Settled only if `max_factor` top contributors cannot reach `threshold`.
+    fn is_settled(&self, contributors: &[Contributor], remaining: Option<u32>) -> bool {
+        ShareBounds::of_top(contributors, self.max_factor as usize, remaining)
+            .is_some_and(|bounds| bounds.rounded_upper() < self.threshold)
+    }
+}
+
+/// Gini coefficient of contributions. Single contributor is treated as fully concentrated.
+pub struct Gini {
+    pub threshold: f32,
+}
+
+impl BusFactorMetric for Gini {
+    fn measure(&self, contributors: &[Contributor]) -> Option<Measurement> {
+        let total = total_contributions(contributors)? as f64;
+        let n = contributors.len() as f64;
+        if contributors.len() == 1 {
+            return Some(top_contributor_measurement(contributors, 1.0, self.threshold));
+        }
+        // Contributors are sorted in desc order, so the rank in asc order is `n - i`
+        let weighted = contributors
+            .iter()
+            .enumerate()
+            .map(|(i, contributor)| (n - i as f64) * contributor.contributions as f64)
+            .sum::<f64>();
+        let gini = (2.0 * weighted) / (n * total) - (n + 1.0) / n;
+        // Corrected for small populations, so that a single active contributor gives 1.0
+        let score = round(gini * n / (n - 1.0));
+        Some(top_contributor_measurement(contributors, score, self.threshold))
+    }
+}
+
+/// One minus Shannon entropy of contributions normalized by its maximum.
+pub struct Entropy {
+    pub threshold: f32,
+}
+
+impl BusFactorMetric for Entropy {
+    fn measure(&self, contributors: &[Contributor]) -> Option<Measurement> {
+        let total = total_contributions(contributors)? as f64;
+        if contributors.len() == 1 {
+            return Some(top_contributor_measurement(contributors, 1.0, self.threshold));
+        }
+        let entropy = -contributors
+            .iter()
+            .filter(|contributor| contributor.contributions > 0)
+            .map(|contributor| contributor.contributions as f64 / total)
+            .map(|share| share * share.ln())
+            .sum::<f64>();
+        let score = round(1.0 - entropy / (contributors.len() as f64).ln());
+        Some(top_contributor_measurement(contributors, score, self.threshold))
+    }
+}
+
+/// Herfindahl-Hirschman index of contributions (sum of squared contribution ratios).
+pub struct Herfindahl {
+    pub threshold: f32,
+}
+
+impl BusFactorMetric for Herfindahl {
+    fn measure(&self, contributors: &[Contributor]) -> Option<Measurement> {
+        let total = total_contributions(contributors)? as f64;
+        let hhi = contributors
+            .iter()
+            .map(|contributor| contributor.contributions as f64 / total)
+            .map(|share| share * share)
+            .sum::<f64>();
+        Some(top_contributor_measurement(contributors, round(hhi), self.threshold))
+    }
+}
+
+/// Produces float from range [0.0,1.0] rounded to two decimal points.
+fn calculate_percentage(contributions: u32, total_contributions: u32) -> f32 {
+    round(contributions as f64 / total_contributions as f64)
+}
+
+// Utility functions
+
+fn round(value: f64) -> f32 {
+    ((value * 100.0).round() / 100.0) as f32
+}
+
+fn total_contributions(contributors: &[Contributor]) -> Option<u32> {
+    let total = contributors
+        .iter()
+        .map(|contributor| contributor.contributions)
+        .sum::<u32>();
+    (total > 0).then_some(total)
+}
+
+fn top_contributor_measurement(contributors: &[Contributor], score: f32, threshold: f32) -> Measurement {
+    Measurement {
+        score,
+        contributors: contributors.iter().take(1).map(|c| c.name.clone()).collect(),
+        reached: score >= threshold,
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+fn contributors(contributions: &[u32]) -> Vec<Contributor> {
+    contributions
+        .iter()
+        .enumerate()
+        .map(|(i, c)| Contributor::new(format!("{}", (b'a' + i as u8) as char), *c))
+        .collect()
+}
+
+#[test]
+fn top_share_test() {
+    let metric = TopShare { threshold: 0.6 };
+    let measurement = metric.measure(&contributors(&[7, 2, 1])).unwrap();
+    assert_eq!(measurement.score, 0.7);
+    assert_eq!(measurement.contributors, vec!["a"]);
+    assert!(measurement.reached);
+}
+
+#[test]
+fn truck_factor_test() {
+    let metric = TruckFactor {
+        threshold: 0.8,
+        max_factor: 2,
+    };
+    let measurement = metric.measure(&contributors(&[5, 4, 1])).unwrap();
+    assert_eq!(measurement.score, 0.9);
+    assert_eq!(measurement.contributors, vec!["a", "b"]);
+    assert!(measurement.reached);
+
+    let measurement = metric.measure(&contributors(&[4, 3, 3])).unwrap();
+    assert_eq!(measurement.contributors, vec!["a", "b", "c"]);
+    assert!(!measurement.reached);
+}
+
+#[test]
+fn gini_test() {
+    let metric = Gini { threshold: 0.5 };
+    assert_eq!(metric.measure(&contributors(&[5, 5, 5])).unwrap().score, 0.0);
+    assert_eq!(metric.measure(&contributors(&[9, 0, 0])).unwrap().score, 1.0);
+    assert_eq!(metric.measure(&contributors(&[7])).unwrap().score, 1.0);
+    let measurement = metric.measure(&contributors(&[7, 2, 1])).unwrap();
+    assert_eq!(measurement.score, 0.6);
+    assert!(measurement.reached);
+}
+
+#[test]
+fn entropy_test() {
+    let metric = Entropy { threshold: 0.5 };
+    assert_eq!(metric.measure(&contributors(&[5, 5, 5])).unwrap().score, 0.0);
+    assert_eq!(metric.measure(&contributors(&[9, 0, 0])).unwrap().score, 1.0);
+    let measurement = metric.measure(&contributors(&[7, 2, 1])).unwrap();
+    assert_eq!(measurement.score, 0.27);
+    assert!(!measurement.reached);
+}
+
+#[test]
+fn herfindahl_test() {
+    let metric = Herfindahl { threshold: 0.5 };
+    assert_eq!(metric.measure(&contributors(&[5, 5])).unwrap().score, 0.5);
+    let measurement = metric.measure(&contributors(&[7, 2, 1])).unwrap();
+    assert_eq!(measurement.score, 0.54);
+    assert!(measurement.reached);
+}
+
+#[test]
+fn share_bounds_test() {
+    let fetched = contributors(&[6, 2, 2]);
+    assert_eq!(
+        ShareBounds::of_top(&fetched, 1, Some(5)),
+        Some(ShareBounds { lower: 0.3, upper: 0.6 })
+    );
+    assert_eq!(
+        ShareBounds::of_top(&fetched, 1, None),
+        Some(ShareBounds { lower: 0.0, upper: 0.6 })
+    );
+    assert_eq!(ShareBounds::of_top(&fetched, 4, None), None);
+}
+
+#[test]
+fn is_settled_test() {
+    let fetched = contributors(&[6, 2, 2]);
+    assert!(TopShare { threshold: 0.7 }.is_settled(&fetched, None));
+    assert!(!TopShare { threshold: 0.5 }.is_settled(&fetched, None));
+    assert!(TopShare { threshold: 0.5 }.is_settled(&fetched, Some(0)));
+    let metric = TruckFactor {
+        threshold: 0.9,
+        max_factor: 2,
+    };
+    assert!(metric.is_settled(&fetched, None));
+    assert!(!Gini { threshold: 0.9 }.is_settled(&fetched, Some(0)));
+}
+
+#[test]
+fn is_settled_boundary_test() {
+    // Upper bound 0.748 is measured as score 0.75, which reaches the threshold
+    let fetched = contributors(&[748, 126, 126]);
+    let top_share = TopShare { threshold: 0.75 };
+    assert!(!top_share.is_settled(&fetched, None));
+    assert!(top_share.measure(&fetched).unwrap().reached);
+    let truck_factor = TruckFactor {
+        threshold: 0.75,
+        max_factor: 1,
+    };
+    assert!(!truck_factor.is_settled(&fetched, None));
+    assert!(truck_factor.measure(&fetched).unwrap().reached);
+}
+
+#[test]
+fn no_contributions_test() {
+    assert_eq!(TopShare { threshold: 0.5 }.measure(&[]), None);
+    assert_eq!(Herfindahl { threshold: 0.5 }.measure(&contributors(&[0, 0])), None);
+}
@@ -0,0 +1,227 @@
+use log::warn;
+use rand::Rng;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// Maximal number of times a request is re-issued after hitting rate limit.
+const MAX_RATE_LIMITS: u32 = 3;
+
+/// Whether and when failed request is re-issued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    /// Request is not re-issued
+    Never,
+    /// Request is re-issued after exponential backoff
+    Backoff,
+    /// Request hit rate limit and is re-issued after given pause, e.g. advised by `Retry-After` header
+    /// or until rate limit reset. Re-issued requests are not counted as attempts.
+    RateLimited(Duration),
+}
+
+/// Error of request which can be retried.
+pub trait Retryable: Display {
+    /// Errors which may occur after request has been processed (timeouts, internal server errors)
+    /// should be retried only for `idempotent` requests.
+    fn retry(&self, idempotent: bool) -> Retry;
+}
+
+/// Retry policy of failed requests with exponential backoff and full jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximal number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Backoff limit of first retry, doubled with every next one
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy with single attempt.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Random backoff from range [0, min(`max_backoff`, `initial_backoff` * 2^(`attempt` - 1))].
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let limit = self.initial_backoff.saturating_mul(1 << exponent).min(self.max_backoff);
+        let millis = limit.as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+
+    /// Calls `request` until it succeeds, fails with not retryable error, or `max_attempts` is reached.
+    /// Rate limited `request` is re-issued up to `MAX_RATE_LIMITS` times, regardless of `max_attempts`.
+    pub async fn retry<T, E, F, FUT>(&self, description: &str, idempotent: bool, mut request: F) -> Result<T, E>
+    where
+        E: Retryable,
+        F: FnMut() -> FUT,
+        FUT: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        let mut rate_limits = 0;
+        loop {
+            let error = match request().await {
+                Err(error) => error,
+                result => return result,
+            };
+            let pause = match error.retry(idempotent) {
+                Retry::Backoff if attempt < self.max_attempts => {
+                    let backoff = self.backoff(attempt);
+                    warn!(
+                        "{} failed (attempt {} of {}): {}. Retrying in {} ms.",
+                        description,
+                        attempt,
+                        self.max_attempts,
+                        error,
+                        backoff.as_millis()
+                    );
+                    attempt += 1;
+                    backoff
+                }
+                Retry::RateLimited(pause) if rate_limits < MAX_RATE_LIMITS => {
+                    warn!(
+                        "{} hit rate limit: {}. Re-issuing in {} sec.",
+                        description,
+                        error,
+                        pause.as_secs()
+                    );
+                    rate_limits += 1;
+                    pause
+                }
+                _ => return Err(error),
+            };
+            crate::wait::sleep(pause).await;
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+#[derive(Debug)]
+struct TestError(Retry);
+
+#[cfg(test)]
+impl Display for TestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+#[cfg(test)]
+impl Retryable for TestError {
+    fn retry(&self, _idempotent: bool) -> Retry {
+        self.0
+    }
+}
+
+#[test]
+fn backoff_test() {
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(1000),
+    };
+    for _ in 0..100 {
+        assert!(policy.backoff(1) <= Duration::from_millis(100));
+        assert!(policy.backoff(3) <= Duration::from_millis(400));
+        assert!(policy.backoff(10) <= Duration::from_millis(1000));
+        assert!(
+            policy.backoff(100) <= Duration::from_millis(1000),
+            "Backoff should not overflow"
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_test() {
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(1),
+    };
+    let mut attempts = 0;
+    let result = policy
+        .retry("request", true, || {
+            attempts += 1;
+            let result = match attempts {
+                1 => Err(TestError(Retry::Backoff)),
+                _ => Ok(attempts),
+            };
+            async move { result }
+        })
+        .await;
+    assert_eq!(result.ok(), Some(2));
+
+    let mut attempts = 0;
+    let result: Result<(), _> = policy
+        .retry("request", true, || {
+            attempts += 1;
+            async { Err(TestError(Retry::Backoff)) }
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(attempts, 3);
+
+    let mut attempts = 0;
+    let result: Result<(), _> = policy
+        .retry("request", true, || {
+            attempts += 1;
+            async { Err(TestError(Retry::Never)) }
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+
+    let started = tokio::time::Instant::now();
+    let mut attempts = 0;
+    let result: Result<(), _> = policy
+        .retry("request", true, || {
+            attempts += 1;
+            async { Err(TestError(Retry::RateLimited(Duration::from_secs(60)))) }
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        attempts,
+        MAX_RATE_LIMITS + 1,
+        "Rate limited requests should not count as attempts"
+    );
+    assert_eq!(
+        started.elapsed(),
+        Duration::from_secs(60 * MAX_RATE_LIMITS as u64),
+        "Advised pause should not be limited by maximal backoff"
+    );
+
+    let policy = RetryPolicy::none();
+    let mut attempts = 0;
+    let result = policy
+        .retry("request", true, || {
+            attempts += 1;
+            let result = match attempts {
+                1 => Err(TestError(Retry::RateLimited(Duration::from_secs(1)))),
+                _ => Ok(attempts),
+            };
+            async move { result }
+        })
+        .await;
+    assert_eq!(
+        result.ok(),
+        Some(2),
+        "Rate limited request should be re-issued without retries"
+    );
+}
@@ -0,0 +1,150 @@
+use crate::api::ResourceStats;
+use crate::BusFactorResult;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::Instant;
+
+/// Statistics of a finished (or stopped) run.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RunSummary {
+    /// Number of repositories to analyze
+    pub repos_requested: u32,
+    /// Number of repositories returned by search
+    pub repos_searched: u32,
+    /// Number of repositories with calculated bus factor
+    pub repos_analyzed: u32,
+    /// Number of analyzed repositories returned as results
+    pub repos_reported: u32,
+    /// Number of repositories not analyzed because of failures
+    pub repos_failed: u32,
+    /// Number of repositories neither analyzed nor failed, e.g. because run has been stopped
+    pub repos_skipped: u32,
+    /// Request statistics by API resource
+    pub resources: BTreeMap<String, ResourceStats>,
+    pub elapsed_ms: u64,
+}
+
+impl Display for RunSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Repositories: requested {}, searched {}, analyzed {}, reported {}, failed {}, skipped {}",
+            self.repos_requested,
+            self.repos_searched,
+            self.repos_analyzed,
+            self.repos_reported,
+            self.repos_failed,
+            self.repos_skipped
+        )?;
+        for (resource, stats) in &self.resources {
+            writeln!(
+                f,
+                "Requests to {}: {}, rate limit wait: {} ms",
+                resource, stats.requests, stats.rate_limit_wait_ms
+            )?;
+        }
+        write!(f, "Elapsed: {} ms", self.elapsed_ms)
+    }
+}
+
+/// Handle of statistics collected by `BusFactorCalculator`, shared with its result stream.
+#[derive(Clone)]
+pub struct RunStats {
+    counters: Arc<Counters>,
+    client_stats: Arc<dyn Fn() -> BTreeMap<String, ResourceStats> + Send + Sync>,
+}
+
+#[derive(Default)]
+struct Counters {
+    started: Mutex<Option<Instant>>,
+    repos_requested: AtomicU32,
+    repos_searched: AtomicU32,
+    repos_analyzed: AtomicU32,
+    repos_reported: AtomicU32,
+    repos_failed: AtomicU32,
+}
+
+impl RunStats {
+    pub(crate) fn new(client_stats: Arc<dyn Fn() -> BTreeMap<String, ResourceStats> + Send + Sync>) -> Self {
+        RunStats {
+            counters: Arc::new(Counters::default()),
+            client_stats,
+        }
+    }
+
+    pub(crate) fn start(&self, repos_requested: u32) {
+        *self.counters.started.lock().unwrap() = Some(Instant::now());
+        self.counters.repos_requested.store(repos_requested, Ordering::Relaxed);
+    }
+
+    pub(crate) fn searched(&self) {
+        self.counters.repos_searched.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts result before it is filtered out, or reported.
+    pub(crate) fn calculated(&self, result: &BusFactorResult) {
+        match result {
+            Ok(_) => self.counters.repos_analyzed.fetch_add(1, Ordering::Relaxed),
+            Err(failure) => self
+                .counters
+                .repos_failed
+                .fetch_add(failure.failed_repos(), Ordering::Relaxed),
+        };
+    }
+
+    pub(crate) fn reported(&self, result: &BusFactorResult) {
+        if result.is_ok() {
+            self.counters.repos_reported.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Summary of the run so far.
+    pub fn summary(&self) -> RunSummary {
+        let counters = &self.counters;
+        let repos_requested = counters.repos_requested.load(Ordering::Relaxed);
+        let repos_analyzed = counters.repos_analyzed.load(Ordering::Relaxed);
+        let repos_failed = counters.repos_failed.load(Ordering::Relaxed);
+        let elapsed = counters.started.lock().unwrap().map(|started| started.elapsed());
+        RunSummary {
+            repos_requested,
+            repos_searched: counters.repos_searched.load(Ordering::Relaxed),
+            repos_analyzed,
+            repos_reported: counters.repos_reported.load(Ordering::Relaxed),
+            repos_failed,
+            repos_skipped: repos_requested.saturating_sub(repos_analyzed + repos_failed),
+            resources: (self.client_stats)(),
+            elapsed_ms: elapsed.map_or(0, |elapsed| elapsed.as_millis() as u64),
+        }
+    }
+}
+
+// Tests
+
+#[test]
+fn run_summary_display_test() {
+    let summary = RunSummary {
+        repos_requested: 10,
+        repos_searched: 10,
+        repos_analyzed: 8,
+        repos_reported: 2,
+        repos_failed: 1,
+        repos_skipped: 1,
+        resources: BTreeMap::from([(
+            "core".to_string(),
+            ResourceStats {
+                requests: 12,
+                rate_limit_wait_ms: 500,
+            },
+        )]),
+        elapsed_ms: 1500,
+    };
+    assert_eq!(
+        summary.to_string(),
+        "Repositories: requested 10, searched 10, analyzed 8, reported 2, failed 1, skipped 1\n\
+         Requests to core: 12, rate limit wait: 500 ms\n\
+         Elapsed: 1500 ms"
+    );
+}
@@ -0,0 +1,51 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::task::{JoinError, JoinHandle};
+
+/// Handle of spawned task, aborting the task when dropped,
+/// so tasks do not outlive (and use rate limit of) dropped result stream.
+pub struct AbortOnDrop<T>(JoinHandle<T>);
+
+/// Spawns task aborted when its handle is dropped.
+pub fn spawn<T: Send + 'static>(future: impl Future<Output = T> + Send + 'static) -> AbortOnDrop<T> {
+    AbortOnDrop(tokio::spawn(future))
+}
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// Tests
+
+#[tokio::test]
+async fn abort_on_drop_test() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let finished = Arc::new(AtomicBool::new(false));
+    let task = spawn({
+        let finished = finished.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            finished.store(true, Ordering::SeqCst);
+        }
+    });
+    drop(task);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!finished.load(Ordering::SeqCst), "Dropped task should be aborted");
+
+    let task = spawn(async { 1 });
+    assert_eq!(task.await.ok(), Some(1));
+}
@@ -0,0 +1,46 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+tokio::task_local! {
+    /// Time spent by current task in waits before requests, in microseconds
+    static WAITED_US: Arc<AtomicU64>;
+}
+
+/// Sleeps before request, e.g. for rate limit or retry backoff. Time of the sleep is not latency of the request.
+pub async fn sleep(duration: Duration) {
+    let started = tokio::time::Instant::now();
+    tokio::time::sleep(duration).await;
+    add_wait(started.elapsed());
+}
+
+/// Counts wait made on behalf of current task elsewhere, e.g. in task sending batched requests.
+pub fn add_wait(duration: Duration) {
+    let _ = WAITED_US.try_with(|waited| waited.fetch_add(duration.as_micros() as u64, Ordering::Relaxed));
+}
+
+/// Runs `request`, and returns its output together with time it spent in `sleep`.
+pub async fn with_waits<F: Future>(request: F) -> (F::Output, Duration) {
+    let waited = Arc::new(AtomicU64::new(0));
+    let output = WAITED_US.scope(waited.clone(), request).await;
+    let waited = Duration::from_micros(waited.load(Ordering::Relaxed));
+    // Waits of nested request are waits of enclosing one too
+    add_wait(waited);
+    (output, waited)
+}
+
+// Tests
+
+#[tokio::test(start_paused = true)]
+async fn with_waits_test() {
+    let (output, waited) = with_waits(async {
+        sleep(Duration::from_secs(2)).await;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let (_, nested) = with_waits(sleep(Duration::from_secs(3))).await;
+        nested
+    })
+    .await;
+    assert_eq!(output, Duration::from_secs(3));
+    assert_eq!(waited, Duration::from_secs(5));
+}
@@ -0,0 +1,144 @@
+use crate::{BitbucketClient, Flavor};
+use bus_factor::retry::RetryPolicy;
+use reqwest::header;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use reqwest::ClientBuilder;
+use secrecy::ExposeSecret;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::Once;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Default timeout of a single request.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of latest commits counted.
+const DEFAULT_MAX_HISTORY_COMMITS: u32 = 1000;
+
+pub struct BitbucketClientBuilder {
+    client_builder: ClientBuilder,
+    flavor: Flavor,
+    bitbucket_url: Option<String>,
+    workspace: Option<String>,
+    headers: HeaderMap,
+    anonymous_contributors: bool,
+    max_history_commits: u32,
+    retry_policy: RetryPolicy,
+}
+
+impl Default for BitbucketClientBuilder {
+    fn default() -> Self {
+        Self {
+            client_builder: ClientBuilder::default().timeout(DEFAULT_REQUEST_TIMEOUT),
+            flavor: Flavor::Cloud,
+            bitbucket_url: None,
+            workspace: None,
+            headers: HeaderMap::from_iter([(header::USER_AGENT, HeaderValue::from_static("curl"))]),
+            anonymous_contributors: false,
+            max_history_commits: DEFAULT_MAX_HISTORY_COMMITS,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+impl BitbucketClientBuilder {
+    /// App password of Bitbucket Cloud account (or password of Bitbucket Data Center account), sent with Basic auth.
+    pub fn try_with_app_password(
+        self,
+        username: impl AsRef<str>,
+        password: secrecy::SecretString,
+    ) -> bus_factor::api::Result<BitbucketClientBuilder> {
+        let credentials = base64::encode(format!("{}:{}", username.as_ref(), password.expose_secret()));
+        let value = format!("Basic {}", credentials);
+        Ok(self.try_with_header(header::AUTHORIZATION, value)?)
+    }
+
+    /// OAuth access token (or HTTP access token of Bitbucket Data Center), sent as Bearer token.
+    pub fn try_with_oauth_token(self, token: secrecy::SecretString) -> bus_factor::api::Result<BitbucketClientBuilder> {
+        let value = format!("Bearer {}", token.expose_secret());
+        Ok(self.try_with_header(header::AUTHORIZATION, value)?)
+    }
+
+    /// Use REST API of self-hosted Bitbucket Data Center (or Server) instead of Bitbucket Cloud.
+    /// Its URL, e.g. `https://bitbucket.example.com/rest/api/1.0`, has to be set with `with_bitbucket_url`.
+    pub fn with_data_center(mut self, data_center: bool) -> BitbucketClientBuilder {
+        self.flavor = if data_center { Flavor::DataCenter } else { Flavor::Cloud };
+        self
+    }
+
+    /// API URL. `https://api.bitbucket.org/2.0` by default for Bitbucket Cloud.
+    pub fn with_bitbucket_url<STR: AsRef<str>>(mut self, url: STR) -> BitbucketClientBuilder {
+        self.bitbucket_url = Some(url.as_ref().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Workspace (Bitbucket Cloud) or project key (Bitbucket Data Center) of listed repositories.
+    /// All accessible repositories are listed by default.
+    pub fn with_workspace(mut self, workspace: Option<String>) -> BitbucketClientBuilder {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Include commit authors without account, named by their commit email.
+    pub fn with_anonymous_contributors(mut self, anonymous_contributors: bool) -> BitbucketClientBuilder {
+        self.anonymous_contributors = anonymous_contributors;
+        self
+    }
+
+    /// Number of latest commits of default branch counted. 1000 by default.
+    pub fn with_max_history_commits(mut self, max_history_commits: u32) -> BitbucketClientBuilder {
+        self.max_history_commits = max_history_commits;
+        self
+    }
+
+    /// Retry policy of requests failed with transient error or rate limit. `RetryPolicy::default()` by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> BitbucketClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Timeout of a single request. 30 sec by default.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> BitbucketClientBuilder {
+        self.client_builder = self.client_builder.timeout(timeout);
+        self
+    }
+
+    fn try_with_header(mut self, key: HeaderName, val: impl AsRef<str>) -> anyhow::Result<BitbucketClientBuilder> {
+        let mut val = HeaderValue::from_str(val.as_ref())?;
+        val.set_sensitive(key != header::USER_AGENT);
+        self.headers.insert(key, val);
+        Ok(self)
+    }
+
+    pub fn build(self) -> bus_factor::api::Result<BitbucketClient> {
+        let bitbucket_url = match (self.bitbucket_url, &self.flavor) {
+            (Some(url), _) => url,
+            (None, Flavor::Cloud) => "https://api.bitbucket.org/2.0".to_string(),
+            (None, Flavor::DataCenter) => {
+                return Err(bus_factor::api::Error::Error(
+                    "Bitbucket Data Center URL is required".to_string(),
+                ))
+            }
+        };
+        let client = self
+            .client_builder
+            .default_headers(self.headers)
+            .build()
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(BitbucketClient {
+            client,
+            flavor: self.flavor,
+            bitbucket_url,
+            workspace: self.workspace,
+            anonymous_contributors: self.anonymous_contributors,
+            max_history_commits: self.max_history_commits,
+            retry_policy: self.retry_policy,
+            next_pages: Mutex::new(HashMap::new()),
+            requests: AtomicU64::new(0),
+            ignored_language_warning: Once::new(),
+        })
+    }
+}
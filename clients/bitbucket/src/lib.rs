@@ -0,0 +1,365 @@
+mod builder;
+mod payload;
+
+use async_trait::async_trait;
+use bus_factor::api::ContributorsPage;
+use bus_factor::api::RepoDetails;
+use bus_factor::api::ResourceStats;
+use bus_factor::api::Sort;
+use bus_factor::commits::{AuthorCommits, CommitCounts};
+use bus_factor::http::{read_response, Error, Result};
+use bus_factor::retry::RetryPolicy;
+use log::{debug, warn};
+use payload::Page;
+use reqwest::Client;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+use tokio::sync::Mutex;
+
+pub use builder::BitbucketClientBuilder;
+
+/// Maximal number of items in a page of Bitbucket API response.
+const MAX_PAGE: u32 = 100;
+
+/// Bitbucket deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    /// Bitbucket Cloud REST API 2.0, paginated with `next` links
+    Cloud,
+    /// Bitbucket Data Center (and Server) REST API 1.0, paginated with `start` of next page
+    DataCenter,
+}
+
+/// Client of Bitbucket Cloud or Bitbucket Data Center API. Bitbucket has no contributors endpoint,
+/// so contributors are commit authors of default branch, counted by paging its commits.
+pub struct BitbucketClient {
+    client: Client,
+    flavor: Flavor,
+    bitbucket_url: String,
+    workspace: Option<String>,
+    anonymous_contributors: bool,
+    max_history_commits: u32,
+    retry_policy: RetryPolicy,
+    /// Links to following pages of repositories listings, by link to their first page
+    next_pages: Mutex<HashMap<String, Vec<Option<String>>>>,
+    /// Number of sent requests
+    requests: AtomicU64,
+    /// Warning about language ignored by Bitbucket Data Center, logged once
+    ignored_language_warning: Once,
+}
+
+#[derive(Debug)]
+pub struct BitbucketRepo {
+    name: String,
+    details: RepoDetails,
+    /// Commits counted on first contributors request
+    authors: Mutex<Option<AuthorCommits>>,
+}
+
+impl BitbucketRepo {
+    fn new(name: String, details: RepoDetails) -> Self {
+        BitbucketRepo {
+            name,
+            details,
+            authors: Mutex::new(None),
+        }
+    }
+}
+
+impl bus_factor::api::Repo for BitbucketRepo {
+    type T = String;
+    fn name(&self) -> Self::T {
+        self.name.clone()
+    }
+
+    fn details(&self) -> RepoDetails {
+        self.details.clone()
+    }
+}
+
+#[async_trait]
+impl bus_factor::api::Client<BitbucketRepo, MAX_PAGE, MAX_PAGE, 1> for BitbucketClient {
+    async fn top_repos(
+        &self,
+        lang: String,
+        page: u32,
+        per_page: u32,
+        order: Sort,
+    ) -> bus_factor::api::Result<Vec<BitbucketRepo>> {
+        self.get_top_repos(lang, page, per_page, order)
+            .await
+            .map_err(Error::into)
+    }
+
+    async fn top_contributors(
+        &self,
+        repo: &BitbucketRepo,
+        page: u32,
+        per_page: u32,
+    ) -> bus_factor::api::Result<ContributorsPage> {
+        let mut authors = repo.authors.lock().await;
+        if authors.is_none() {
+            *authors = Some(self.count_commits(repo).await?);
+        }
+        let authors = authors.as_ref().expect("Commits counted");
+        Ok(authors.page(page, per_page))
+    }
+
+    fn stats(&self) -> BTreeMap<String, ResourceStats> {
+        let stats = ResourceStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            rate_limit_wait_ms: 0,
+        };
+        BTreeMap::from([("bitbucket".to_string(), stats)])
+    }
+}
+
+impl BitbucketClient {
+    /// Bitbucket Cloud repositories are filtered by language and sorted only by last update.
+    /// Bitbucket Data Center knows neither languages nor popularity, so all repositories are listed in name order,
+    /// ignoring order and language (with a warning).
+    async fn get_top_repos(&self, lang: String, page: u32, per_page: u32, order: Sort) -> Result<Vec<BitbucketRepo>> {
+        match self.flavor {
+            Flavor::Cloud => {
+                let sort = match order {
+                    Sort::Updated => "-updated_on",
+                    order => {
+                        return Err(format!("Bitbucket repositories cannot be sorted by {}", order.as_ref()).into())
+                    }
+                };
+                let request_url = match &self.workspace {
+                    Some(workspace) => format!("{}/repositories/{}", self.bitbucket_url, workspace),
+                    None => format!("{}/repositories", self.bitbucket_url),
+                };
+                let query = [
+                    ("q", format!("language=\"{}\"", lang.to_lowercase())),
+                    ("sort", sort.to_string()),
+                    ("pagelen", per_page.to_string()),
+                ];
+                let first_url = url_with_query(&request_url, &query)?;
+                self.get_repos_page::<payload::CloudRepo>(first_url, page).await
+            }
+            Flavor::DataCenter => {
+                self.ignored_language_warning.call_once(|| {
+                    warn!(
+                        "Bitbucket Data Center neither filters repositories by language nor sorts them. \
+                        Ignoring language {}, listing all repositories in name order.",
+                        lang
+                    )
+                });
+                let request_url = match &self.workspace {
+                    Some(project) => format!("{}/projects/{}/repos", self.bitbucket_url, project),
+                    None => format!("{}/repos", self.bitbucket_url),
+                };
+                let first_url = url_with_query(&request_url, &[("limit", per_page.to_string())])?;
+                self.get_repos_page::<payload::DataCenterRepo>(first_url, page).await
+            }
+        }
+    }
+
+    /// Bitbucket pages are reached through links of their predecessors, so preceding pages are fetched if not known yet.
+    async fn get_repos_page<REPO>(&self, first_url: String, page: u32) -> Result<Vec<BitbucketRepo>>
+    where
+        REPO: DeserializeOwned + Into<BitbucketRepo>,
+    {
+        let mut next_pages = self.next_pages.lock().await;
+        let next_pages = next_pages.entry(first_url.clone()).or_default();
+        let preceding_pages = page.saturating_sub(1) as usize;
+        while next_pages.len() < preceding_pages {
+            let url = match page_url(&first_url, next_pages, next_pages.len()) {
+                Some(url) => url,
+                None => return Ok(Vec::new()),
+            };
+            let (_, next) = self.get_page::<REPO>(&url).await?;
+            next_pages.push(next);
+        }
+        let url = match page_url(&first_url, next_pages, preceding_pages) {
+            Some(url) => url,
+            None => return Ok(Vec::new()),
+        };
+        let (repos, next) = self.get_page::<REPO>(&url).await?;
+        if next_pages.len() == preceding_pages {
+            next_pages.push(next);
+        }
+        Ok(repos.into_iter().map(REPO::into).collect())
+    }
+
+    /// Counts commits of default branch by author, up to `max_history_commits` commits.
+    async fn count_commits(&self, repo: &BitbucketRepo) -> Result<AuthorCommits> {
+        let (owner, slug) = (&repo.details.owner, &repo.name);
+        match self.flavor {
+            Flavor::Cloud => {
+                // Repository without main branch has no commits
+                if repo.details.default_branch.is_empty() {
+                    return Ok(AuthorCommits::default());
+                }
+                let request_url = format!("{}/repositories/{}/{}/commits", self.bitbucket_url, owner, slug);
+                let query = [
+                    ("include", repo.details.default_branch.clone()),
+                    ("pagelen", MAX_PAGE.to_string()),
+                ];
+                let first_url = url_with_query(&request_url, &query)?;
+                self.count_commits_from(repo, first_url, payload::CloudCommit::author_name)
+                    .await
+            }
+            Flavor::DataCenter => {
+                let request_url = format!("{}/projects/{}/repos/{}/commits", self.bitbucket_url, owner, slug);
+                let first_url = url_with_query(&request_url, &[("limit", MAX_PAGE.to_string())])?;
+                self.count_commits_from(repo, first_url, payload::DataCenterCommit::author_name)
+                    .await
+            }
+        }
+    }
+
+    async fn count_commits_from<COMMIT: DeserializeOwned>(
+        &self,
+        repo: &BitbucketRepo,
+        first_url: String,
+        author_name: fn(COMMIT, bool) -> Option<String>,
+    ) -> Result<AuthorCommits> {
+        let mut counts = CommitCounts::default();
+        let mut url = first_url;
+        let truncated = loop {
+            let (commits, next) = self.get_page::<COMMIT>(&url).await?;
+            for commit in commits {
+                counts.add(author_name(commit, self.anonymous_contributors));
+            }
+            url = match next {
+                Some(next) => next,
+                None => break false,
+            };
+            if counts.commits() >= self.max_history_commits {
+                debug!(
+                    "Counted {} commits of {}. Skipping older history.",
+                    counts.commits(),
+                    repo.details.full_name
+                );
+                break true;
+            }
+        };
+        Ok(counts.into_authors(truncated))
+    }
+
+    /// Values of page, and link to the next one.
+    async fn get_page<T: DeserializeOwned>(&self, url: &str) -> Result<(Vec<T>, Option<String>)> {
+        let page: Page<T> = self.get(url).await?;
+        let next = next_url(url, &page)?;
+        Ok((page.values, next))
+    }
+
+    /// GET request retried according to `RetryPolicy`.
+    async fn get<PAYLOAD: DeserializeOwned>(&self, request_url: &str) -> Result<PAYLOAD> {
+        let description = format!("GET {}", request_url);
+        self.retry_policy
+            .retry(&description, true, || self.get_once(request_url))
+            .await
+    }
+
+    async fn get_once<PAYLOAD: DeserializeOwned>(&self, request_url: &str) -> Result<PAYLOAD> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let response = self.client.get(request_url).send().await?;
+        read_response(response, payload::error_message).await
+    }
+}
+
+fn url_with_query(request_url: &str, query: &[(&str, String)]) -> Result<String> {
+    Url::parse_with_params(request_url, query)
+        .map(String::from)
+        .map_err(|err| format!("Invalid URL {}: {}", request_url, err).into())
+}
+
+/// Link to page following `page` of `url`. Bitbucket Cloud links it directly,
+/// while Bitbucket Data Center gives its `start`.
+fn next_url<T>(url: &str, page: &Page<T>) -> Result<Option<String>> {
+    if let Some(next) = &page.next {
+        return Ok(Some(next.clone()));
+    }
+    match (page.is_last_page, page.next_page_start) {
+        (Some(false), Some(start)) => {
+            let mut next = Url::parse(url).map_err(|err| format!("Invalid URL {}: {}", url, err))?;
+            let query: Vec<(String, String)> = next
+                .query_pairs()
+                .filter(|(key, _)| key != "start")
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect();
+            next.query_pairs_mut()
+                .clear()
+                .extend_pairs(query)
+                .append_pair("start", &start.to_string());
+            Ok(Some(next.into()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Link to page with zero-based `index`, if there is such page.
+fn page_url(first_url: &str, next_pages: &[Option<String>], index: usize) -> Option<String> {
+    match index {
+        0 => Some(first_url.to_string()),
+        index => next_pages[index - 1].clone(),
+    }
+}
+
+// Tests
+
+#[test]
+fn next_url_test() {
+    let page = |next: Option<&str>, is_last_page, next_page_start| Page::<()> {
+        values: Vec::new(),
+        next: next.map(str::to_string),
+        is_last_page,
+        next_page_start,
+    };
+    let url = "https://bitbucket.example.com/rest/api/1.0/repos?limit=100&start=100";
+    assert_eq!(
+        next_url(url, &page(None, Some(false), Some(200))).unwrap(),
+        Some("https://bitbucket.example.com/rest/api/1.0/repos?limit=100&start=200".to_string())
+    );
+    assert_eq!(next_url(url, &page(None, Some(true), None)).unwrap(), None);
+    let url = "https://api.bitbucket.org/2.0/repositories?pagelen=100";
+    let next = "https://api.bitbucket.org/2.0/repositories?pagelen=100&after=2022-05-01";
+    assert_eq!(
+        next_url(url, &page(Some(next), None, None)).unwrap(),
+        Some(next.to_string())
+    );
+    assert_eq!(next_url(url, &page(None, None, None)).unwrap(), None);
+}
+
+#[test]
+fn page_url_test() {
+    let next_pages = [Some("page-2".to_string()), None];
+    assert_eq!(page_url("page-1", &next_pages, 0), Some("page-1".to_string()));
+    assert_eq!(page_url("page-1", &next_pages, 1), Some("page-2".to_string()));
+    assert_eq!(page_url("page-1", &next_pages, 2), None);
+}
+
+#[test]
+fn author_name_test() {
+    let cloud = |user: Option<&str>| payload::CloudCommit {
+        author: payload::CloudAuthor {
+            raw: "Jane Doe <jane@example.com>".to_string(),
+            user: user.map(|nickname| payload::CloudUser {
+                nickname: nickname.to_string(),
+            }),
+        },
+    };
+    assert_eq!(cloud(Some("jane")).author_name(false), Some("jane".to_string()));
+    assert_eq!(cloud(None).author_name(false), None);
+    assert_eq!(cloud(None).author_name(true), Some("jane@example.com".to_string()));
+    let data_center = |slug: Option<&str>| payload::DataCenterCommit {
+        author: payload::DataCenterAuthor {
+            name: "Jane Doe".to_string(),
+            email_address: Some("jane@example.com".to_string()),
+            slug: slug.map(str::to_string),
+        },
+    };
+    assert_eq!(data_center(Some("jane")).author_name(false), Some("jane".to_string()));
+    assert_eq!(data_center(None).author_name(false), None);
+    assert_eq!(
+        data_center(None).author_name(true),
+        Some("jane@example.com".to_string())
+    );
+}
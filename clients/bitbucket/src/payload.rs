@@ -0,0 +1,209 @@
+use serde::Deserialize;
+
+/// Page of Bitbucket Cloud (`next` link) or Bitbucket Data Center (`nextPageStart`) response.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub values: Vec<T>,
+    /// Link to next page of Bitbucket Cloud response
+    pub next: Option<String>,
+    /// Whether it is the last page of Bitbucket Data Center response
+    pub is_last_page: Option<bool>,
+    /// Start of next page of Bitbucket Data Center response
+    pub next_page_start: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudRepo {
+    pub slug: String,
+    pub full_name: String,
+    pub workspace: Workspace,
+    pub links: CloudLinks,
+    /// Missing for empty repository
+    pub mainbranch: Option<Branch>,
+    pub updated_on: Option<String>,
+    /// Repository of which this is a fork
+    pub parent: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Workspace {
+    pub slug: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudLinks {
+    pub html: Link,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Link {
+    pub href: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Branch {
+    pub name: String,
+}
+
+impl From<CloudRepo> for crate::BitbucketRepo {
+    fn from(repo: CloudRepo) -> Self {
+        crate::BitbucketRepo::new(
+            repo.slug,
+            bus_factor::api::RepoDetails {
+                owner: repo.workspace.slug,
+                full_name: repo.full_name,
+                html_url: repo.links.html.href,
+                default_branch: repo.mainbranch.map(|branch| branch.name).unwrap_or_default(),
+                pushed_at: repo.updated_on,
+                fork: repo.parent.is_some(),
+                ..bus_factor::api::RepoDetails::default()
+            },
+        )
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DataCenterRepo {
+    pub slug: String,
+    pub project: Project,
+    pub links: DataCenterLinks,
+    #[serde(default)]
+    pub archived: bool,
+    /// Repository of which this is a fork
+    pub origin: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Project {
+    pub key: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DataCenterLinks {
+    #[serde(rename = "self", default)]
+    pub self_links: Vec<Link>,
+}
+
+impl From<DataCenterRepo> for crate::BitbucketRepo {
+    fn from(repo: DataCenterRepo) -> Self {
+        crate::BitbucketRepo::new(
+            repo.slug.clone(),
+            bus_factor::api::RepoDetails {
+                full_name: format!("{}/{}", repo.project.key, repo.slug),
+                owner: repo.project.key,
+                html_url: repo
+                    .links
+                    .self_links
+                    .into_iter()
+                    .next()
+                    .map(|link| link.href)
+                    .unwrap_or_default(),
+                archived: repo.archived,
+                fork: repo.origin.is_some(),
+                ..bus_factor::api::RepoDetails::default()
+            },
+        )
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudCommit {
+    pub author: CloudAuthor,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudAuthor {
+    /// Author as in commit, e.g. `Jane Doe <jane@example.com>`
+    #[serde(default)]
+    pub raw: String,
+    /// Bitbucket account of author, if any
+    pub user: Option<CloudUser>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudUser {
+    pub nickname: String,
+}
+
+impl CloudCommit {
+    /// Nickname of commit author, or (for `anonymous` authors without account) their email.
+    pub fn author_name(self, anonymous: bool) -> Option<String> {
+        match self.author.user {
+            Some(user) => Some(user.nickname),
+            None if anonymous => raw_author_email(&self.author.raw),
+            None => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DataCenterCommit {
+    pub author: DataCenterAuthor,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCenterAuthor {
+    pub name: String,
+    pub email_address: Option<String>,
+    /// Present only for authors with Bitbucket account
+    pub slug: Option<String>,
+}
+
+impl DataCenterCommit {
+    /// Slug of commit author, or (for `anonymous` authors without account) their email.
+    pub fn author_name(self, anonymous: bool) -> Option<String> {
+        match self.author.slug {
+            Some(slug) => Some(slug),
+            None if anonymous => self.author.email_address.or(Some(self.author.name)),
+            None => None,
+        }
+    }
+}
+
+/// Email of raw author, e.g. `jane@example.com` of `Jane Doe <jane@example.com>`, or the whole raw author.
+fn raw_author_email(raw: &str) -> Option<String> {
+    let email = match (raw.find('<'), raw.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &raw[start + 1..end],
+        _ => raw,
+    };
+    Some(email.trim().to_string()).filter(|email| !email.is_empty())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudErrorBody {
+    pub error: CloudError,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CloudError {
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DataCenterErrorBody {
+    pub errors: Vec<CloudError>,
+}
+
+/// Message of Bitbucket Cloud error, or joined messages of Bitbucket Data Center errors.
+pub fn error_message(body: &str) -> Option<String> {
+    if let Ok(cloud) = serde_json::from_str::<CloudErrorBody>(body) {
+        return Some(cloud.error.message);
+    }
+    let data_center = serde_json::from_str::<DataCenterErrorBody>(body).ok()?;
+    let messages: Vec<String> = data_center.errors.into_iter().map(|error| error.message).collect();
+    Some(messages.join(" "))
+}
+
+// Tests
+
+#[test]
+fn raw_author_email_test() {
+    assert_eq!(
+        raw_author_email("Jane Doe <jane@example.com>"),
+        Some("jane@example.com".to_string())
+    );
+    assert_eq!(raw_author_email("jane"), Some("jane".to_string()));
+    assert_eq!(raw_author_email(""), None);
+}
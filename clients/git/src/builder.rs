@@ -0,0 +1,72 @@
+use crate::LocalGitClient;
+use chrono::{Duration, NaiveDate};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+/// Default number of latest commits counted.
+const DEFAULT_MAX_HISTORY_COMMITS: u32 = 1000;
+
+pub struct LocalGitClientBuilder {
+    repos_dir: PathBuf,
+    branch: Option<String>,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    max_history_commits: u32,
+}
+
+impl LocalGitClientBuilder {
+    /// Directory of cloned (or bare) git repositories, or a single repository.
+    pub fn new(repos_dir: impl Into<PathBuf>) -> Self {
+        LocalGitClientBuilder {
+            repos_dir: repos_dir.into(),
+            branch: None,
+            since: None,
+            until: None,
+            max_history_commits: DEFAULT_MAX_HISTORY_COMMITS,
+        }
+    }
+
+    /// Branch whose history is counted. `HEAD` by default.
+    pub fn with_branch(mut self, branch: Option<String>) -> LocalGitClientBuilder {
+        self.branch = branch;
+        self
+    }
+
+    /// Count only commits committed on this day or later.
+    pub fn with_since(mut self, since: Option<NaiveDate>) -> LocalGitClientBuilder {
+        self.since = since;
+        self
+    }
+
+    /// Count only commits committed on this day or earlier.
+    pub fn with_until(mut self, until: Option<NaiveDate>) -> LocalGitClientBuilder {
+        self.until = until;
+        self
+    }
+
+    /// Number of latest commits counted. 1000 by default.
+    pub fn with_max_history_commits(mut self, max_history_commits: u32) -> LocalGitClientBuilder {
+        self.max_history_commits = max_history_commits;
+        self
+    }
+
+    pub fn build(self) -> bus_factor::api::Result<LocalGitClient> {
+        if !self.repos_dir.is_dir() {
+            return Err(bus_factor::api::Error::Error(format!(
+                "Repositories directory {} not found",
+                self.repos_dir.display()
+            )));
+        }
+        Ok(LocalGitClient {
+            repos_dir: self.repos_dir,
+            branch: self.branch,
+            since: self.since.map(|since| since.and_hms(0, 0, 0).timestamp()),
+            // Until end of the day
+            until: self
+                .until
+                .map(|until| (until + Duration::days(1)).and_hms(0, 0, 0).timestamp()),
+            max_history_commits: self.max_history_commits,
+            repos: Mutex::new(None),
+        })
+    }
+}
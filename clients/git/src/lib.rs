@@ -0,0 +1,342 @@
+mod builder;
+
+use async_trait::async_trait;
+use bus_factor::api::ContributorsPage;
+use bus_factor::api::RepoDetails;
+use bus_factor::api::RequestContext;
+use bus_factor::api::Sort;
+use bus_factor::commits::{AuthorCommits, CommitCounts};
+use chrono::{TimeZone, Utc};
+use gix::traverse::commit::simple::Sorting;
+use log::{debug, warn};
+use std::cmp::Reverse;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+pub use builder::LocalGitClientBuilder;
+
+/// Maximal number of items in a page.
+const MAX_PAGE: u32 = 100;
+
+/// Client of git repositories cloned to local directory, analyzed offline without any API.
+/// Contributors are commit authors of branch (`HEAD` by default), named by their email as mapped by `.mailmap`.
+pub struct LocalGitClient {
+    repos_dir: PathBuf,
+    branch: Option<String>,
+    /// Commit time of the earliest counted commits, in seconds since epoch
+    since: Option<i64>,
+    /// Commit time following the latest counted commits, in seconds since epoch
+    until: Option<i64>,
+    max_history_commits: u32,
+    /// Repositories found in directory on first request
+    repos: Mutex<Option<Arc<Vec<LocalGitRepo>>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalGitRepo {
+    name: String,
+    path: PathBuf,
+    /// Commit time of branch tip, if there is any commit
+    last_commit: Option<i64>,
+    details: RepoDetails,
+    /// Commits counted on first contributors request, shared by clones of listed repository
+    authors: Arc<Mutex<Option<AuthorCommits>>>,
+}
+
+impl bus_factor::api::Repo for LocalGitRepo {
+    type T = String;
+    fn name(&self) -> Self::T {
+        self.name.clone()
+    }
+
+    fn details(&self) -> RepoDetails {
+        self.details.clone()
+    }
+}
+
+#[async_trait]
+impl bus_factor::api::Client<LocalGitRepo, MAX_PAGE, MAX_PAGE, 1> for LocalGitClient {
+    async fn top_repos(
+        &self,
+        lang: String,
+        page: u32,
+        per_page: u32,
+        order: Sort,
+    ) -> bus_factor::api::Result<Vec<LocalGitRepo>> {
+        self.get_top_repos(lang, page, per_page, order)
+            .await
+            .map_err(crate::Error::into)
+    }
+
+    async fn top_contributors(
+        &self,
+        repo: &LocalGitRepo,
+        page: u32,
+        per_page: u32,
+    ) -> bus_factor::api::Result<ContributorsPage> {
+        let mut authors = repo.authors.lock().await;
+        if authors.is_none() {
+            let path = repo.path.clone();
+            let branch = self.branch.clone();
+            let (since, until, max_history_commits) = (self.since, self.until, self.max_history_commits);
+            *authors = Some(
+                spawn_blocking(move || count_commits(&path, branch.as_deref(), since, until, max_history_commits))
+                    .await?,
+            );
+        }
+        let authors = authors.as_ref().expect("Commits counted");
+        Ok(authors.page(page, per_page))
+    }
+}
+
+impl LocalGitClient {
+    /// Local repositories have no language nor popularity, so all of them are listed, sorted only by their latest commit.
+    async fn get_top_repos(&self, _lang: String, page: u32, per_page: u32, order: Sort) -> Result<Vec<LocalGitRepo>> {
+        if order != Sort::Updated {
+            return Err(format!("Local repositories cannot be sorted by {}", order.as_ref()).into());
+        }
+        let repos = self.list_repos().await?;
+        let start = (page.saturating_sub(1) * per_page) as usize;
+        Ok(repos.iter().skip(start).take(per_page as usize).cloned().collect())
+    }
+
+    async fn list_repos(&self) -> Result<Arc<Vec<LocalGitRepo>>> {
+        let mut repos = self.repos.lock().await;
+        if let Some(repos) = repos.as_ref() {
+            return Ok(repos.clone());
+        }
+        let repos_dir = self.repos_dir.clone();
+        let branch = self.branch.clone();
+        let mut found = spawn_blocking(move || find_repos(&repos_dir, branch.as_deref())).await?;
+        found.sort_by_key(|repo| (Reverse(repo.last_commit), repo.name.clone()));
+        Ok(repos.insert(Arc::new(found)).clone())
+    }
+}
+
+/// Repositories in subdirectories of `repos_dir`, or `repos_dir` itself if it is a (possibly bare) repository.
+/// Repositories which cannot be read are skipped with a warning.
+fn find_repos(repos_dir: &Path, branch: Option<&str>) -> Result<Vec<LocalGitRepo>> {
+    if let Ok(repo) = gix::open(repos_dir) {
+        return Ok(vec![local_repo(&repo, repos_dir.to_path_buf(), branch)?]);
+    }
+    let entries = std::fs::read_dir(repos_dir).map_err(git_error(repos_dir))?;
+    let mut repos = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(git_error(repos_dir))?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        match gix::open(&path) {
+            Ok(repo) => match local_repo(&repo, path.clone(), branch) {
+                Ok(repo) => repos.push(repo),
+                Err(err) => warn!("Skipping {}, which cannot be read: {}", path.display(), err),
+            },
+            Err(err) => debug!("Skipping {}, which is not a git repository: {}", path.display(), err),
+        }
+    }
+    Ok(repos)
+}
+
+fn local_repo(repo: &gix::Repository, path: PathBuf, branch: Option<&str>) -> Result<LocalGitRepo> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let default_branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => match repo.head_name().map_err(git_error(&path))? {
+            Some(head) => head.shorten().to_string(),
+            None => String::new(),
+        },
+    };
+    // Missing branch fails when contributors are counted
+    let last_commit = match branch_tip(repo, &path, branch) {
+        Ok(Some(tip)) => Some(commit_time(repo, &path, tip)?),
+        _ => None,
+    };
+    let details = RepoDetails {
+        full_name: name.clone(),
+        html_url: path.display().to_string(),
+        default_branch,
+        pushed_at: last_commit
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+            .map(|time| time.to_rfc3339()),
+        ..RepoDetails::default()
+    };
+    Ok(LocalGitRepo {
+        name,
+        path,
+        last_commit,
+        details,
+        authors: Arc::new(Mutex::new(None)),
+    })
+}
+
+/// Commit of `branch` (or `HEAD`), if there is any.
+fn branch_tip(repo: &gix::Repository, path: &Path, branch: Option<&str>) -> Result<Option<gix::ObjectId>> {
+    match branch {
+        Some(branch) => {
+            let mut reference = repo.find_reference(branch).map_err(|err| Error::BranchNotFound {
+                path: path.display().to_string(),
+                branch: branch.to_string(),
+                message: err.to_string(),
+            })?;
+            let tip = reference.peel_to_id_in_place().map_err(git_error(path))?;
+            Ok(Some(tip.detach()))
+        }
+        None => {
+            if repo.head().map_err(git_error(path))?.is_unborn() {
+                return Ok(None);
+            }
+            Ok(Some(repo.head_id().map_err(git_error(path))?.detach()))
+        }
+    }
+}
+
+fn commit_time(repo: &gix::Repository, path: &Path, id: gix::ObjectId) -> Result<i64> {
+    let commit = repo
+        .find_object(id)
+        .map_err(git_error(path))?
+        .try_into_commit()
+        .map_err(git_error(path))?;
+    Ok(commit.time().map_err(git_error(path))?.seconds)
+}
+
+/// Counts commits of branch by author resolved with `.mailmap`, from the latest one, up to `max_history_commits` commits.
+fn count_commits(
+    path: &Path,
+    branch: Option<&str>,
+    since: Option<i64>,
+    until: Option<i64>,
+    max_history_commits: u32,
+) -> Result<AuthorCommits> {
+    let repo = gix::open(path).map_err(git_error(path))?;
+    let tip = match branch_tip(&repo, path, branch)? {
+        Some(tip) => tip,
+        None => return Ok(AuthorCommits::default()),
+    };
+    let mailmap = repo.open_mailmap();
+    let sorting = match since {
+        Some(seconds) => Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds },
+        None => Sorting::ByCommitTimeNewestFirst,
+    };
+    let walk = repo.rev_walk([tip]).sorting(sorting).all().map_err(git_error(path))?;
+    let mut counts = CommitCounts::default();
+    let mut truncated = false;
+    for info in walk {
+        let commit = info.map_err(git_error(path))?.object().map_err(git_error(path))?;
+        let time = commit.time().map_err(git_error(path))?.seconds;
+        if !in_range(time, since, until) {
+            continue;
+        }
+        if counts.commits() >= max_history_commits {
+            debug!(
+                "Counted {} commits of {}. Skipping older history.",
+                counts.commits(),
+                path.display()
+            );
+            truncated = true;
+            break;
+        }
+        let author = mailmap.resolve(commit.author().map_err(git_error(path))?);
+        counts.add(author_name(&author));
+    }
+    Ok(counts.into_authors(truncated))
+}
+
+fn in_range(time: i64, since: Option<i64>, until: Option<i64>) -> bool {
+    since.is_none_or(|since| time >= since) && until.is_none_or(|until| time < until)
+}
+
+/// Author named by email, or by name if email is missing.
+fn author_name(author: &gix::actor::Signature) -> Option<String> {
+    let name = if author.email.is_empty() {
+        author.name.to_string()
+    } else {
+        author.email.to_string()
+    };
+    Some(name).filter(|name| !name.is_empty())
+}
+
+/// Runs blocking git operation outside of async runtime.
+async fn spawn_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|err| Error::Error(format!("Git task failed: {}", err)))?
+}
+
+// Result and Errors
+
+pub(crate) type Result<T> = std::result::Result<T, crate::Error>;
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Error, Debug)]
+pub(crate) enum Error {
+    #[error("Error: {0}")]
+    Error(String),
+    #[error("Git error: {message} [{path}]")]
+    GitError { path: String, message: String },
+    #[error("Branch {branch} not found: {message} [{path}]")]
+    BranchNotFound {
+        path: String,
+        branch: String,
+        message: String,
+    },
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::Error(msg)
+    }
+}
+
+fn git_error<E: Display>(path: &Path) -> impl FnOnce(E) -> Error + '_ {
+    move |err| Error::GitError {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    }
+}
+
+impl From<Error> for bus_factor::api::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::BranchNotFound { ref path, .. } => {
+                let context = RequestContext::new(Some(path.clone()), None, err.to_string());
+                bus_factor::api::Error::NotFound(context)
+            }
+            err => bus_factor::api::Error::Error(err.to_string()),
+        }
+    }
+}
+
+// Tests
+
+#[test]
+fn in_range_test() {
+    assert!(in_range(100, None, None));
+    assert!(in_range(100, Some(100), Some(101)));
+    assert!(!in_range(99, Some(100), None));
+    assert!(!in_range(101, None, Some(101)));
+}
+
+#[test]
+fn author_name_test() {
+    let author = |name: &str, email: &str| gix::actor::Signature {
+        name: name.into(),
+        email: email.into(),
+        time: gix::date::Time::default(),
+    };
+    assert_eq!(
+        author_name(&author("Jane Doe", "jane@example.com")),
+        Some("jane@example.com".to_string())
+    );
+    assert_eq!(author_name(&author("Jane Doe", "")), Some("Jane Doe".to_string()));
+    assert_eq!(author_name(&author("", "")), None);
+}
@@ -0,0 +1,99 @@
+use crate::GiteaClient;
+use bus_factor::retry::RetryPolicy;
+use reqwest::header;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use reqwest::ClientBuilder;
+use secrecy::ExposeSecret;
+use std::sync::atomic::AtomicU64;
+use std::time::Duration;
+
+/// Default timeout of a single request.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of latest commits counted.
+const DEFAULT_MAX_HISTORY_COMMITS: u32 = 1000;
+
+pub struct GiteaClientBuilder {
+    client_builder: ClientBuilder,
+    gitea_url: String,
+    headers: HeaderMap,
+    anonymous_contributors: bool,
+    max_history_commits: u32,
+    retry_policy: RetryPolicy,
+}
+
+impl Default for GiteaClientBuilder {
+    fn default() -> Self {
+        Self {
+            client_builder: ClientBuilder::default().timeout(DEFAULT_REQUEST_TIMEOUT),
+            gitea_url: "https://codeberg.org/api/v1".to_string(),
+            headers: HeaderMap::from_iter([(header::USER_AGENT, HeaderValue::from_static("curl"))]),
+            anonymous_contributors: false,
+            max_history_commits: DEFAULT_MAX_HISTORY_COMMITS,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+impl GiteaClientBuilder {
+    /// Access token of Gitea account.
+    pub fn try_with_token(self, token: secrecy::SecretString) -> bus_factor::api::Result<GiteaClientBuilder> {
+        let value = format!("token {}", token.expose_secret());
+        Ok(self.try_with_header(header::AUTHORIZATION, value)?)
+    }
+
+    /// API URL, e.g. `https://gitea.example.com/api/v1`. `https://codeberg.org/api/v1` by default.
+    pub fn with_gitea_url<STR: AsRef<str>>(mut self, url: STR) -> GiteaClientBuilder {
+        self.gitea_url = url.as_ref().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Include commit authors without account, named by their commit email.
+    pub fn with_anonymous_contributors(mut self, anonymous_contributors: bool) -> GiteaClientBuilder {
+        self.anonymous_contributors = anonymous_contributors;
+        self
+    }
+
+    /// Number of latest commits of default branch counted. 1000 by default.
+    pub fn with_max_history_commits(mut self, max_history_commits: u32) -> GiteaClientBuilder {
+        self.max_history_commits = max_history_commits;
+        self
+    }
+
+    /// Retry policy of requests failed with transient error or rate limit. `RetryPolicy::default()` by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> GiteaClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Timeout of a single request. 30 sec by default.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> GiteaClientBuilder {
+        self.client_builder = self.client_builder.timeout(timeout);
+        self
+    }
+
+    fn try_with_header(mut self, key: HeaderName, val: impl AsRef<str>) -> anyhow::Result<GiteaClientBuilder> {
+        let mut val = HeaderValue::from_str(val.as_ref())?;
+        val.set_sensitive(key != header::USER_AGENT);
+        self.headers.insert(key, val);
+        Ok(self)
+    }
+
+    pub fn build(self) -> bus_factor::api::Result<GiteaClient> {
+        let client = self
+            .client_builder
+            .default_headers(self.headers)
+            .build()
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(GiteaClient {
+            client,
+            gitea_url: self.gitea_url,
+            anonymous_contributors: self.anonymous_contributors,
+            max_history_commits: self.max_history_commits,
+            retry_policy: self.retry_policy,
+            requests: AtomicU64::new(0),
+        })
+    }
+}
@@ -0,0 +1,270 @@
+mod builder;
+mod payload;
+
+use async_trait::async_trait;
+use bus_factor::api::ContributorsPage;
+use bus_factor::api::RepoDetails;
+use bus_factor::api::ResourceStats;
+use bus_factor::api::Sort;
+use bus_factor::commits::{AuthorCommits, CommitCounts};
+use bus_factor::http::{read_response, Error, Result};
+use bus_factor::retry::RetryPolicy;
+use log::debug;
+use reqwest::header::{HeaderMap, LINK};
+use reqwest::Client;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::Mutex;
+
+pub use builder::GiteaClientBuilder;
+
+/// Default maximal number of items in a page of Gitea API response.
+const MAX_PAGE: u32 = 50;
+
+/// Client of Gitea (and Forgejo) API. Gitea has no contributors endpoint,
+/// so contributors are commit authors of default branch, counted by paging its commits.
+pub struct GiteaClient {
+    client: Client,
+    gitea_url: String,
+    anonymous_contributors: bool,
+    max_history_commits: u32,
+    retry_policy: RetryPolicy,
+    /// Number of sent requests
+    requests: AtomicU64,
+}
+
+#[derive(Debug)]
+pub struct GiteaRepo {
+    name: String,
+    /// Repository without commits
+    empty: bool,
+    details: RepoDetails,
+    /// Commits counted on first contributors request
+    authors: Mutex<Option<AuthorCommits>>,
+}
+
+impl GiteaRepo {
+    fn new(name: String, empty: bool, details: RepoDetails) -> Self {
+        GiteaRepo {
+            name,
+            empty,
+            details,
+            authors: Mutex::new(None),
+        }
+    }
+}
+
+impl bus_factor::api::Repo for GiteaRepo {
+    type T = String;
+    fn name(&self) -> Self::T {
+        self.name.clone()
+    }
+
+    fn details(&self) -> RepoDetails {
+        self.details.clone()
+    }
+}
+
+#[async_trait]
+impl bus_factor::api::Client<GiteaRepo, MAX_PAGE, MAX_PAGE, 1> for GiteaClient {
+    async fn top_repos(
+        &self,
+        lang: String,
+        page: u32,
+        per_page: u32,
+        order: Sort,
+    ) -> bus_factor::api::Result<Vec<GiteaRepo>> {
+        self.get_top_repos(lang, page, per_page, order)
+            .await
+            .map_err(Error::into)
+    }
+
+    async fn top_contributors(
+        &self,
+        repo: &GiteaRepo,
+        page: u32,
+        per_page: u32,
+    ) -> bus_factor::api::Result<ContributorsPage> {
+        let mut authors = repo.authors.lock().await;
+        if authors.is_none() {
+            *authors = Some(self.count_commits(repo).await?);
+        }
+        let authors = authors.as_ref().expect("Commits counted");
+        Ok(authors.page(page, per_page))
+    }
+
+    fn stats(&self) -> BTreeMap<String, ResourceStats> {
+        let stats = ResourceStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            rate_limit_wait_ms: 0,
+        };
+        BTreeMap::from([("gitea".to_string(), stats)])
+    }
+}
+
+impl GiteaClient {
+    /// Gitea does not search repositories by language, so repositories are searched by their topic named after it.
+    async fn get_top_repos(&self, lang: String, page: u32, per_page: u32, order: Sort) -> Result<Vec<GiteaRepo>> {
+        let sort = match order {
+            Sort::Stars => "stars",
+            Sort::Forks => "forks",
+            Sort::Updated => "updated",
+            order => return Err(format!("Gitea repositories cannot be sorted by {}", order.as_ref()).into()),
+        };
+        let request_url = format!("{}/repos/search", self.gitea_url);
+        let query = [
+            ("q", lang.to_lowercase()),
+            ("topic", "true".to_string()),
+            ("sort", sort.to_string()),
+            ("order", "desc".to_string()),
+            ("page", page.to_string()),
+            ("limit", per_page.to_string()),
+        ];
+        let response: payload::SearchRepos = self.get(&request_url, &query).await?;
+        Ok(response.data.into_iter().map(GiteaRepo::from).collect())
+    }
+
+    /// Counts commits of default branch by author, up to `max_history_commits` commits.
+    /// Instance may limit pages below requested `MAX_PAGE` commits, so paging ends with the last page
+    /// reported by headers of response, or with an empty page.
+    async fn count_commits(&self, repo: &GiteaRepo) -> Result<AuthorCommits> {
+        if repo.empty {
+            return Ok(AuthorCommits::default());
+        }
+        let request_url = format!("{}/repos/{}/{}/commits", self.gitea_url, repo.details.owner, repo.name);
+        let mut counts = CommitCounts::default();
+        let mut fetched = 0;
+        let mut page = 1;
+        let truncated = loop {
+            let query = [
+                ("sha", repo.details.default_branch.clone()),
+                ("stat", "false".to_string()),
+                ("verification", "false".to_string()),
+                ("files", "false".to_string()),
+                ("page", page.to_string()),
+                ("limit", MAX_PAGE.to_string()),
+            ];
+            let (commits, headers) = match self
+                .get_with_headers::<Vec<payload::Commit>>(&request_url, &query)
+                .await
+            {
+                Ok(response) => response,
+                // Repository emptied after search
+                Err(Error::StatusError(error)) if error.status == StatusCode::CONFLICT => break false,
+                Err(error) => return Err(error),
+            };
+            if commits.is_empty() {
+                break false;
+            }
+            fetched += commits.len() as u64;
+            let has_next_page = has_next_page(&headers, fetched);
+            let mut commits = commits.into_iter();
+            for commit in commits.by_ref() {
+                if counts.commits() >= self.max_history_commits {
+                    break;
+                }
+                counts.add(commit.author_name(self.anonymous_contributors));
+            }
+            let remaining = commits.next().is_some();
+            if !remaining && has_next_page == Some(false) {
+                break false;
+            }
+            if counts.commits() >= self.max_history_commits {
+                debug!(
+                    "Counted {} commits of {}. Skipping older history.",
+                    counts.commits(),
+                    repo.details.full_name
+                );
+                break true;
+            }
+            page += 1;
+        };
+        Ok(counts.into_authors(truncated))
+    }
+
+    /// GET request retried according to `RetryPolicy`.
+    async fn get<PAYLOAD: DeserializeOwned>(&self, request_url: &str, query: &[(&str, String)]) -> Result<PAYLOAD> {
+        Ok(self.get_with_headers(request_url, query).await?.0)
+    }
+
+    /// GET request retried according to `RetryPolicy`, with headers of its response.
+    async fn get_with_headers<PAYLOAD: DeserializeOwned>(
+        &self,
+        request_url: &str,
+        query: &[(&str, String)],
+    ) -> Result<(PAYLOAD, HeaderMap)> {
+        let description = format!("GET {}", request_url);
+        self.retry_policy
+            .retry(&description, true, || self.get_once(request_url, query))
+            .await
+    }
+
+    async fn get_once<PAYLOAD: DeserializeOwned>(
+        &self,
+        request_url: &str,
+        query: &[(&str, String)],
+    ) -> Result<(PAYLOAD, HeaderMap)> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let response = self.client.get(request_url).query(query).send().await?;
+        let headers = response.headers().clone();
+        Ok((read_response(response, payload::error_message).await?, headers))
+    }
+}
+
+/// Whether items follow `fetched` ones, according to `Link` header (with `next` link), or `X-Total-Count` header.
+/// Unknown without both of them.
+fn has_next_page(headers: &HeaderMap, fetched: u64) -> Option<bool> {
+    if let Some(link) = headers.get(LINK).and_then(|link| link.to_str().ok()) {
+        return Some(link.contains("rel=\"next\""));
+    }
+    headers
+        .get("x-total-count")
+        .and_then(|total| total.to_str().ok())
+        .and_then(|total| total.parse::<u64>().ok())
+        .map(|total| fetched < total)
+}
+
+// Tests
+
+#[test]
+fn author_name_test() {
+    let commit = |login: Option<&str>| payload::Commit {
+        commit: payload::CommitDetails {
+            author: Some(payload::CommitAuthor {
+                name: "Jane Doe".to_string(),
+                email: "jane@example.com".to_string(),
+            }),
+        },
+        author: login.map(|login| payload::User {
+            login: login.to_string(),
+        }),
+    };
+    assert_eq!(commit(Some("jane")).author_name(false), Some("jane".to_string()));
+    assert_eq!(commit(None).author_name(false), None);
+    assert_eq!(commit(Some("")).author_name(true), Some("jane@example.com".to_string()));
+}
+
+#[test]
+fn has_next_page_test() {
+    let headers = |pairs: &[(&'static str, &'static str)]| {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect::<HeaderMap>()
+    };
+    let next = r#"<https://codeberg.org/api/v1/repos/o/r/commits?page=3>; rel="next",<https://codeberg.org/api/v1/repos/o/r/commits?page=9>; rel="last""#;
+    let last = r#"<https://codeberg.org/api/v1/repos/o/r/commits?page=1>; rel="first",<https://codeberg.org/api/v1/repos/o/r/commits?page=2>; rel="prev""#;
+    assert_eq!(
+        has_next_page(&headers(&[("link", next), ("x-total-count", "60")]), 60),
+        Some(true)
+    );
+    assert_eq!(
+        has_next_page(&headers(&[("link", last), ("x-total-count", "60")]), 30),
+        Some(false)
+    );
+    assert_eq!(has_next_page(&headers(&[("x-total-count", "60")]), 30), Some(true));
+    assert_eq!(has_next_page(&headers(&[("x-total-count", "60")]), 60), Some(false));
+    assert_eq!(has_next_page(&headers(&[]), 30), None);
+}
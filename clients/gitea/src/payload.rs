@@ -0,0 +1,99 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct SearchRepos {
+    pub data: Vec<Repo>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Repo {
+    pub name: String,
+    pub full_name: String,
+    pub owner: User,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub stars_count: u32,
+    #[serde(default)]
+    pub forks_count: u32,
+    #[serde(default)]
+    pub open_issues_count: u32,
+    #[serde(default)]
+    pub default_branch: String,
+    /// Whether repository has no commits
+    #[serde(default)]
+    pub empty: bool,
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct User {
+    pub login: String,
+}
+
+impl From<Repo> for crate::GiteaRepo {
+    fn from(repo: Repo) -> Self {
+        crate::GiteaRepo::new(
+            repo.name,
+            repo.empty,
+            bus_factor::api::RepoDetails {
+                owner: repo.owner.login,
+                full_name: repo.full_name,
+                html_url: repo.html_url,
+                stars: repo.stars_count,
+                forks: repo.forks_count,
+                open_issues: repo.open_issues_count,
+                default_branch: repo.default_branch,
+                pushed_at: repo.updated_at,
+                archived: repo.archived,
+                fork: repo.fork,
+            },
+        )
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Commit {
+    pub commit: CommitDetails,
+    /// Account of commit author, missing if author email does not belong to any account
+    pub author: Option<User>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitDetails {
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub email: String,
+}
+
+impl Commit {
+    /// Login of commit author, or (for `anonymous` authors without account) their email.
+    pub fn author_name(self, anonymous: bool) -> Option<String> {
+        match self.author {
+            // Deleted or unknown accounts are reported with empty login
+            Some(user) if !user.login.is_empty() => Some(user.login),
+            _ if anonymous => self.commit.author.map(|author| match author.email.is_empty() {
+                true => author.name,
+                false => author.email,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ErrorBody {
+    pub message: String,
+}
+
+pub fn error_message(body: &str) -> Option<String> {
+    serde_json::from_str::<ErrorBody>(body).ok().map(|body| body.message)
+}
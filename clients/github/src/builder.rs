@@ -1,9 +1,14 @@
+use crate::limiter::Pacing;
 use crate::limiter::RateLimit;
 use crate::limiter::RateLimiter;
 use crate::payload::RateLimitBody;
 use crate::payload::RateLimitResource;
 use crate::payload::RateLimitResources;
 use crate::GithubClient;
+use crate::GithubGraphqlClient;
+use crate::RetryPolicy;
+use bus_factor::clock::Clock;
+use bus_factor::clock::SystemClock;
 use reqwest::header;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
@@ -12,20 +17,42 @@ use reqwest::Client;
 use reqwest::ClientBuilder;
 use secrecy::ExposeSecret;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+/// Default timeout of a single request.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of latest commits counted by `GithubGraphqlClient`.
+const DEFAULT_MAX_HISTORY_COMMITS: u32 = 1000;
+
+/// GraphQL rate limit of authenticated users, assumed when API does not report it.
+const DEFAULT_GRAPHQL_POINTS: u32 = 5000;
+
 pub struct GithubClientBuilder {
     client_builder: ClientBuilder,
     github_url: String,
     headers: HeaderMap,
+    anonymous_contributors: bool,
+    retry_policy: RetryPolicy,
+    pacing: Pacing,
+    reserve: f32,
+    clock: Arc<dyn Clock>,
+    max_history_commits: u32,
 }
 
 impl Default for GithubClientBuilder {
     fn default() -> Self {
         let builder = Self {
-            client_builder: ClientBuilder::default(),
+            client_builder: ClientBuilder::default().timeout(DEFAULT_REQUEST_TIMEOUT),
             github_url: "https://api.github.com".to_string(),
             headers: HeaderMap::default(),
+            anonymous_contributors: false,
+            retry_policy: RetryPolicy::default(),
+            pacing: Pacing::Burst,
+            reserve: 0.0,
+            clock: Arc::new(SystemClock),
+            max_history_commits: DEFAULT_MAX_HISTORY_COMMITS,
         };
         builder
             .try_with_header(header::USER_AGENT, "curl")
@@ -49,6 +76,49 @@ impl GithubClientBuilder {
         self
     }
 
+    /// Include contributors without GitHub account, named by their commit email.
+    pub fn with_anonymous_contributors(mut self, anonymous_contributors: bool) -> GithubClientBuilder {
+        self.anonymous_contributors = anonymous_contributors;
+        self
+    }
+
+    /// Retry policy of failed requests. `RetryPolicy::default()` by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> GithubClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Pacing of requests within rate limits. `Pacing::Burst` by default.
+    pub fn with_pacing(mut self, pacing: Pacing) -> GithubClientBuilder {
+        self.pacing = pacing;
+        self
+    }
+
+    /// Ratio of rate limits left unused by client, e.g. for interactive use of the same token. 0 by default.
+    pub fn with_reserve(mut self, reserve: f32) -> GithubClientBuilder {
+        self.reserve = reserve;
+        self
+    }
+
+    /// Timeout of a single request, from connecting until the response body is read. 30 sec by default.
+    /// Timed out requests are retried according to `RetryPolicy`.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> GithubClientBuilder {
+        self.client_builder = self.client_builder.timeout(timeout);
+        self
+    }
+
+    /// Clock of rate limiters. `SystemClock` by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> GithubClientBuilder {
+        self.clock = clock;
+        self
+    }
+
+    /// Number of latest commits of default branch counted by `GithubGraphqlClient`. 1000 by default.
+    pub fn with_max_history_commits(mut self, max_history_commits: u32) -> GithubClientBuilder {
+        self.max_history_commits = max_history_commits;
+        self
+    }
+
     fn try_with_header(mut self, key: HeaderName, val: impl AsRef<str>) -> anyhow::Result<GithubClientBuilder> {
         let val = HeaderValue::from_str(val.as_ref())?;
         self.headers.insert(key, val);
@@ -56,33 +126,109 @@ impl GithubClientBuilder {
     }
 
     pub async fn build(self) -> bus_factor::api::Result<GithubClient> {
-        let client = self
-            .client_builder
-            .default_headers(self.headers)
-            .build()
-            .map_err(|err| anyhow::anyhow!(err))?;
+        let client = http_client(self.client_builder, self.headers)?;
         let github_url = self.github_url;
-        let rate_limit = get_rate_limit(&client, &github_url)
-            .await
-            .map_err(|err| anyhow::anyhow!(err))?;
-        let repos_limiter = rate_limit.search.into();
-        let contrib_limiter = rate_limit.core.into();
-        Ok(GithubClient::new(client, github_url, repos_limiter, contrib_limiter))
+        let rate_limit = get_rate_limit(&client, &github_url).await?;
+        let repos_limiter = rate_limiter(rate_limit.search, self.pacing, self.reserve, self.clock.clone());
+        let contrib_limiter = rate_limiter(rate_limit.core, self.pacing, self.reserve, self.clock);
+        Ok(GithubClient::new(
+            client,
+            github_url,
+            repos_limiter,
+            contrib_limiter,
+            self.anonymous_contributors,
+            self.retry_policy,
+        ))
+    }
+
+    /// Client of GraphQL API, with rate limiter of GraphQL points.
+    pub async fn build_graphql(self) -> bus_factor::api::Result<GithubGraphqlClient> {
+        let client = http_client(self.client_builder, self.headers)?;
+        let rate_limit = get_rate_limit(&client, &self.github_url).await?;
+        let points = rate_limit.graphql.unwrap_or(RateLimitResource {
+            limit: DEFAULT_GRAPHQL_POINTS,
+            remaining: DEFAULT_GRAPHQL_POINTS,
+            reset: self.clock.now() + 3600,
+        });
+        let limiter = rate_limiter(points, self.pacing, self.reserve, self.clock);
+        Ok(GithubGraphqlClient::new(
+            client,
+            graphql_url(&self.github_url),
+            limiter,
+            self.anonymous_contributors,
+            self.max_history_commits,
+            self.retry_policy,
+        ))
     }
 }
 
-async fn get_rate_limit(client: &Client, github_url: impl Into<String>) -> reqwest::Result<RateLimitResources> {
+fn http_client(client_builder: ClientBuilder, headers: HeaderMap) -> bus_factor::api::Result<Client> {
+    let client = client_builder
+        .default_headers(headers)
+        .build()
+        .map_err(|err| anyhow::anyhow!(err))?;
+    Ok(client)
+}
+
+/// GraphQL endpoint of REST API URL, e.g. `https://api.github.com/graphql`,
+/// or `https://github.example.com/api/graphql` for GitHub Enterprise `https://github.example.com/api/v3`.
+fn graphql_url(github_url: &str) -> String {
+    let github_url = github_url.trim_end_matches('/');
+    match github_url.strip_suffix("/v3") {
+        Some(api_url) => format!("{}/graphql", api_url),
+        None => format!("{}/graphql", github_url),
+    }
+}
+
+async fn get_rate_limit(client: &Client, github_url: impl Into<String>) -> crate::Result<RateLimitResources> {
     let request_url = format!("{}/rate_limit", github_url.into());
     let response = client.get(request_url).send().await?;
     crate::read_response::<RateLimitBody>(response)
-        .await
+        .await?
         .map(|resources| resources.resources)
+        .ok_or_else(|| "No rate limit content".to_string().into())
 }
 
-impl From<RateLimitResource> for RateLimiter {
-    fn from(limit_resource: RateLimitResource) -> Self {
-        let remaining = std::cmp::max(limit_resource.remaining, 1) - 1;
-        let limit = RateLimit::new(limit_resource.limit, remaining, limit_resource.reset);
-        RateLimiter::new(Arc::new(Mutex::new(limit)))
-    }
+fn rate_limiter(limit_resource: RateLimitResource, pacing: Pacing, reserve: f32, clock: Arc<dyn Clock>) -> RateLimiter {
+    let limit = rate_limit(limit_resource, clock.as_ref())
+        .with_pacing(pacing)
+        .with_reserve(reserve);
+    RateLimiter::new(Arc::new(Mutex::new(limit)), clock)
+}
+
+/// Limit without remaining requests, unless its reset has already passed.
+fn rate_limit(limit_resource: RateLimitResource, clock: &dyn Clock) -> RateLimit {
+    let remaining = match limit_resource.reset < clock.now() {
+        true => limit_resource.limit,
+        false => limit_resource.remaining,
+    };
+    let remaining = std::cmp::max(remaining, 1) - 1;
+    RateLimit::new(limit_resource.limit, remaining, limit_resource.reset)
+}
+
+// Tests
+
+#[test]
+fn rate_limit_test() {
+    let clock = bus_factor::clock::SimulatedClock::new(1_000_000);
+    let resource = |remaining, reset| RateLimitResource {
+        limit: 10,
+        remaining,
+        reset,
+    };
+    let limit = rate_limit(resource(5, 1001), &clock);
+    assert_eq!(limit.limit(), 10);
+    assert_eq!(limit.remaining(), 4);
+    assert_eq!(limit.reset(), 1001);
+    let limit = rate_limit(resource(0, 999), &clock);
+    assert_eq!(limit.remaining(), 9, "Passed reset should restore limit");
+}
+
+#[test]
+fn graphql_url_test() {
+    assert_eq!(graphql_url("https://api.github.com"), "https://api.github.com/graphql");
+    assert_eq!(
+        graphql_url("https://github.example.com/api/v3/"),
+        "https://github.example.com/api/graphql"
+    );
 }
@@ -0,0 +1,617 @@
+mod payload;
+
+use crate::limiter::RateLimiter;
+use crate::DEFAULT_SECONDARY_RATE_LIMIT_PAUSE;
+use crate::{header_value, read_response, Result, RetryPolicy};
+use async_trait::async_trait;
+use bus_factor::api::ContributorsPage;
+use bus_factor::api::RepoDetails;
+use bus_factor::api::RequestContext;
+use bus_factor::api::RequestKind;
+use bus_factor::api::ResourceStats;
+use bus_factor::api::Sort;
+use bus_factor::commits::{AuthorCommits, CommitCounts};
+use bus_factor::retry::Retry;
+use bus_factor::task::AbortOnDrop;
+use log::debug;
+use payload::RateLimitData;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// Number of commits in a page of history.
+const HISTORY_PAGE: u32 = 100;
+
+/// Maximal number of repositories which histories are requested in one query.
+const MAX_HISTORY_BATCH: usize = 20;
+
+/// Time of collecting history requests into one batch.
+const HISTORY_BATCH_WINDOW: Duration = Duration::from_millis(20);
+
+const HISTORY_FIELDS: &str = "pageInfo { endCursor hasNextPage } nodes { author { name email user { login } } }";
+
+const RATE_LIMIT_FIELDS: &str = "rateLimit { limit cost remaining resetAt }";
+
+/// Type of error of query exceeding rate limit, reported in response with 200 status.
+const RATE_LIMITED: &str = "RATE_LIMITED";
+
+/// Client of GitHub GraphQL API. Search fetches repositories together with first page of their histories,
+/// and further pages of histories of different repositories are fetched in batched queries.
+/// Contributors are commit authors of default branch, counted from its history.
+pub struct GithubGraphqlClient {
+    inner: Arc<Inner>,
+    history_requests: mpsc::UnboundedSender<HistoryRequest>,
+    /// Task batching history requests, aborted when client is dropped
+    _batching: AbortOnDrop<()>,
+    /// Cursors of search results by search query, locked separately for every query
+    search_cursors: Mutex<HashMap<String, Arc<Mutex<SearchCursors>>>>,
+    anonymous_contributors: bool,
+    max_history_commits: u32,
+}
+
+/// Cursors following pages of search results of one query. `None` cursor follows the last page.
+type SearchCursors = Vec<Option<String>>;
+
+struct Inner {
+    client: Client,
+    graphql_url: String,
+    /// Limiter of points of GraphQL rate limit
+    limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+}
+
+#[derive(Debug)]
+pub struct GithubGraphqlRepo {
+    name: String,
+    details: RepoDetails,
+    /// First page of history fetched with repository, taken when commits are counted
+    history: std::sync::Mutex<Option<payload::History>>,
+    /// Commits counted on first contributors request
+    authors: Mutex<Option<AuthorCommits>>,
+}
+
+/// Request of history page, answered by batching task.
+struct HistoryRequest {
+    owner: String,
+    name: String,
+    after: Option<String>,
+    /// History page, and time the batch waited before it was sent
+    response: oneshot::Sender<(bus_factor::api::Result<payload::History>, Duration)>,
+}
+
+impl GithubGraphqlRepo {
+    fn new(name: String, details: RepoDetails, history: Option<payload::History>) -> Self {
+        GithubGraphqlRepo {
+            name,
+            details,
+            history: std::sync::Mutex::new(history),
+            authors: Mutex::new(None),
+        }
+    }
+}
+
+impl bus_factor::api::Repo for GithubGraphqlRepo {
+    type T = String;
+    fn name(&self) -> Self::T {
+        self.name.clone()
+    }
+
+    fn details(&self) -> RepoDetails {
+        self.details.clone()
+    }
+}
+
+#[async_trait]
+impl bus_factor::api::Client<GithubGraphqlRepo, 100, 100, 1> for GithubGraphqlClient {
+    async fn top_repos(
+        &self,
+        lang: String,
+        page: u32,
+        per_page: u32,
+        order: Sort,
+    ) -> bus_factor::api::Result<Vec<GithubGraphqlRepo>> {
+        self.get_top_repos(lang, page, per_page, order)
+            .await
+            .map_err(crate::Error::into)
+    }
+
+    async fn top_contributors(
+        &self,
+        repo: &GithubGraphqlRepo,
+        page: u32,
+        per_page: u32,
+    ) -> bus_factor::api::Result<ContributorsPage> {
+        let mut authors = repo.authors.lock().await;
+        if authors.is_none() {
+            *authors = Some(self.count_commits(repo).await?);
+        }
+        let authors = authors.as_ref().expect("Commits counted");
+        Ok(authors.page(page, per_page))
+    }
+
+    async fn remaining_rate_limit(&self, _kind: RequestKind) -> Option<f32> {
+        Some(self.inner.limiter.remaining_ratio().await)
+    }
+
+    fn stats(&self) -> BTreeMap<String, ResourceStats> {
+        BTreeMap::from([("graphql".to_string(), self.inner.limiter.stats())])
+    }
+}
+
+impl GithubGraphqlClient {
+    /// Spawns task batching history requests, running until client is dropped.
+    pub(crate) fn new(
+        client: Client,
+        graphql_url: String,
+        limiter: RateLimiter,
+        anonymous_contributors: bool,
+        max_history_commits: u32,
+        retry_policy: RetryPolicy,
+    ) -> Self {
+        let inner = Arc::new(Inner {
+            client,
+            graphql_url,
+            limiter,
+            retry_policy,
+        });
+        let (history_requests, receiver) = mpsc::unbounded_channel();
+        let batching = bus_factor::task::spawn(batch_history_requests(inner.clone(), receiver));
+        GithubGraphqlClient {
+            inner,
+            history_requests,
+            _batching: batching,
+            search_cursors: Mutex::new(HashMap::new()),
+            anonymous_contributors,
+            max_history_commits,
+        }
+    }
+
+    /// GraphQL search is paginated with cursors, so cursors of preceding pages are fetched if not known yet.
+    /// Requests of the same query wait for cursors of preceding pages, but requested page is fetched without waiting.
+    async fn get_top_repos(
+        &self,
+        lang: String,
+        page: u32,
+        per_page: u32,
+        order: Sort,
+    ) -> Result<Vec<GithubGraphqlRepo>> {
+        let query = format!("language:{} sort:{}", lang, sort_qualifier(&order));
+        let cursors = self
+            .search_cursors
+            .lock()
+            .await
+            .entry(query.clone())
+            .or_default()
+            .clone();
+        let preceding_pages = page.saturating_sub(1) as usize;
+        let after = {
+            let mut cursors = cursors.lock().await;
+            while cursors.len() < preceding_pages {
+                let after = match cursor_after(&cursors, cursors.len()) {
+                    Some(after) => after,
+                    None => return Ok(Vec::new()),
+                };
+                let search = self.search(&query, per_page, after).await?;
+                cursors.push(next_cursor(&search.page_info));
+            }
+            match cursor_after(&cursors, preceding_pages) {
+                Some(after) => after,
+                None => return Ok(Vec::new()),
+            }
+        };
+        let search = self.search(&query, per_page, after).await?;
+        let mut cursors = cursors.lock().await;
+        if cursors.len() == preceding_pages {
+            cursors.push(next_cursor(&search.page_info));
+        }
+        Ok(search
+            .nodes
+            .into_iter()
+            .flatten()
+            .map(GithubGraphqlRepo::from)
+            .collect())
+    }
+
+    async fn search(&self, query: &str, per_page: u32, after: Option<String>) -> Result<payload::Search> {
+        let variables = json!({ "query": query, "first": per_page, "after": after });
+        // Search connection and history connection of every repository
+        let cost = estimated_cost(1 + per_page);
+        let response = self
+            .inner
+            .query::<payload::SearchData>(&search_query(), variables, cost)
+            .await?;
+        if let Some(error) = response.errors.first() {
+            return Err(self.inner.graphql_error(error).into());
+        }
+        let data = response
+            .data
+            .ok_or_else(|| format!("No data of {}", self.inner.graphql_url))?;
+        Ok(data.search)
+    }
+
+    /// Counts commits of default branch by author, up to `max_history_commits` commits.
+    async fn count_commits(&self, repo: &GithubGraphqlRepo) -> bus_factor::api::Result<AuthorCommits> {
+        let mut history = repo.history.lock().unwrap().take();
+        let mut after = None;
+        let mut counts = CommitCounts::default();
+        let truncated = loop {
+            let page = match history.take() {
+                Some(page) => page,
+                None => self.history_page(repo, after.take()).await?,
+            };
+            for commit in page.nodes {
+                counts.add(commit.author_name(self.anonymous_contributors));
+            }
+            if !page.page_info.has_next_page {
+                break false;
+            }
+            if counts.commits() >= self.max_history_commits {
+                debug!(
+                    "Counted {} commits of {}. Skipping older history.",
+                    counts.commits(),
+                    repo.details.full_name
+                );
+                break true;
+            }
+            after = page.page_info.end_cursor;
+        };
+        Ok(counts.into_authors(truncated))
+    }
+
+    async fn history_page(
+        &self,
+        repo: &GithubGraphqlRepo,
+        after: Option<String>,
+    ) -> bus_factor::api::Result<payload::History> {
+        let (response, receiver) = oneshot::channel();
+        let request = HistoryRequest {
+            owner: repo.details.owner.clone(),
+            name: repo.name.clone(),
+            after,
+            response,
+        };
+        let stopped = || bus_factor::api::Error::Error("History requests batching stopped".to_string());
+        self.history_requests.send(request).map_err(|_| stopped())?;
+        let (history, waited) = receiver.await.map_err(|_| stopped())?;
+        // Waits of batch are waits of every batched request
+        bus_factor::wait::add_wait(waited);
+        history
+    }
+}
+
+impl Inner {
+    /// Query retried according to `RetryPolicy`. Rate limit reported in response data updates `limiter`.
+    /// Rate limited query pauses `limiter` until rate limit resets and is re-issued.
+    async fn query<DATA: DeserializeOwned + RateLimitData>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+        cost: u32,
+    ) -> Result<payload::Response<DATA>> {
+        let body = json!({ "query": query, "variables": variables });
+        let description = format!("POST {}", self.graphql_url);
+        // Queries do not modify anything, so they are idempotent
+        self.retry_policy
+            .retry(&description, true, || self.query_once(&body, cost))
+            .await
+    }
+
+    async fn query_once<DATA: DeserializeOwned + RateLimitData>(
+        &self,
+        body: &serde_json::Value,
+        cost: u32,
+    ) -> Result<payload::Response<DATA>> {
+        self.limiter.wait_for(cost).await;
+        let response = self.client.post(&self.graphql_url).json(body).send().await?;
+        let reset = header_value::<i64>(response.headers(), "x-ratelimit-reset");
+        let response: payload::Response<DATA> = read_response(response)
+            .await?
+            .ok_or_else(|| format!("No content of {}", self.graphql_url))?;
+        // Rate limited query is answered with 200 status, error, and no data
+        if let Some(error) = response
+            .errors
+            .iter()
+            .find(|error| error.kind.as_deref() == Some(RATE_LIMITED))
+        {
+            let pause = match reset {
+                Some(reset) => Duration::from_secs((reset - self.limiter.now()).max(0) as u64 + 1),
+                None => Duration::from_secs(DEFAULT_SECONDARY_RATE_LIMIT_PAUSE),
+            };
+            self.limiter.pause(pause).await;
+            let error = GraphqlError {
+                reset,
+                pause: Some(pause),
+                ..self.graphql_error(error)
+            };
+            return Err(error.into());
+        }
+        let rate_limit = response.data.as_ref().and_then(RateLimitData::rate_limit);
+        if let Some(rate_limit) = rate_limit {
+            debug!("Query cost {} points", rate_limit.cost);
+            if let Ok(reset) = chrono::DateTime::parse_from_rfc3339(&rate_limit.reset_at) {
+                self.limiter
+                    .update(rate_limit.limit, rate_limit.remaining, reset.timestamp())
+                    .await;
+            }
+        }
+        Ok(response)
+    }
+
+    /// Fetches pages of histories of batched repositories, and answers each request.
+    /// Query is cancelled once every request is dropped, e.g. by aborted task of dropped result stream.
+    async fn fetch_histories(self: Arc<Self>, mut batch: Vec<HistoryRequest>) {
+        let mut variables = serde_json::Map::new();
+        for (index, request) in batch.iter().enumerate() {
+            variables.insert(format!("owner{}", index), json!(request.owner));
+            variables.insert(format!("name{}", index), json!(request.name));
+            variables.insert(format!("after{}", index), json!(request.after));
+        }
+        let cost = estimated_cost(batch.len() as u32);
+        let query = history_query(batch.len());
+        let query = bus_factor::wait::with_waits(self.query::<payload::HistoryData>(&query, variables.into(), cost));
+        let dropped = async {
+            for request in batch.iter_mut() {
+                request.response.closed().await;
+            }
+        };
+        let (response, waited) = tokio::select! {
+            response = query => response,
+            _ = dropped => {
+                debug!("History requests dropped. Cancelling query of {} histories.", batch.len());
+                return;
+            }
+        };
+        match response {
+            Ok(response) => {
+                let mut repositories = response.data.map(|data| data.repositories).unwrap_or_default();
+                for (index, request) in batch.into_iter().enumerate() {
+                    let alias = format!("repo{}", index);
+                    let error = response
+                        .errors
+                        .iter()
+                        .find(|error| error.path.is_empty() || error.path[0] == alias.as_str());
+                    let result = match error {
+                        Some(error) => Err(self.graphql_error(error).into()),
+                        None => Ok(repositories
+                            .remove(&alias)
+                            .flatten()
+                            .and_then(|repo| repo.default_branch_ref)
+                            .and_then(|branch| branch.target)
+                            .and_then(|target| target.history)
+                            .unwrap_or_default()),
+                    };
+                    // Requester might have been dropped
+                    let _ = request.response.send((result, waited));
+                }
+            }
+            Err(error) => {
+                let error = bus_factor::api::Error::from(error);
+                for request in batch {
+                    let _ = request.response.send((Err(error.clone()), waited));
+                }
+            }
+        }
+    }
+
+    fn graphql_error(&self, error: &payload::ErrorBody) -> GraphqlError {
+        GraphqlError {
+            kind: error.kind.clone(),
+            message: error.message.clone(),
+            url: self.graphql_url.clone(),
+            reset: None,
+            pause: None,
+        }
+    }
+}
+
+/// Collects history requests arriving within `HISTORY_BATCH_WINDOW` into batches sent as single queries.
+async fn batch_history_requests(inner: Arc<Inner>, mut requests: mpsc::UnboundedReceiver<HistoryRequest>) {
+    while let Some(request) = requests.recv().await {
+        let mut batch = vec![request];
+        let window = tokio::time::sleep(HISTORY_BATCH_WINDOW);
+        tokio::pin!(window);
+        while batch.len() < MAX_HISTORY_BATCH {
+            tokio::select! {
+                request = requests.recv() => match request {
+                    Some(request) => batch.push(request),
+                    None => break,
+                },
+                _ = &mut window => break,
+            }
+        }
+        debug!("Requesting {} histories", batch.len());
+        tokio::spawn(inner.clone().fetch_histories(batch));
+    }
+}
+
+/// Estimated cost of query in points: number of requests needed to fulfil its connections divided by 100.
+fn estimated_cost(connections: u32) -> u32 {
+    ((connections as f32 / 100.0).round() as u32).max(1)
+}
+
+/// Cursor following first `pages` pages of search results. `None` if there are no more results.
+fn cursor_after(cursors: &[Option<String>], pages: usize) -> Option<Option<String>> {
+    match pages {
+        0 => Some(None),
+        pages => cursors[pages - 1].clone().map(Some),
+    }
+}
+
+fn next_cursor(page_info: &payload::PageInfo) -> Option<String> {
+    page_info.end_cursor.clone().filter(|_| page_info.has_next_page)
+}
+
+fn sort_qualifier(order: &Sort) -> &'static str {
+    match order {
+        Sort::Stars => "stars",
+        Sort::Forks => "forks",
+        Sort::HelpWantedIssues => "help-wanted-issues",
+        Sort::Updated => "updated",
+    }
+}
+
+fn search_query() -> String {
+    format!(
+        "query($query: String!, $first: Int!, $after: String) {{ {} \
+         search(query: $query, type: REPOSITORY, first: $first, after: $after) {{ \
+         pageInfo {{ endCursor hasNextPage }} \
+         nodes {{ ... on Repository {{ name nameWithOwner owner {{ login }} url stargazerCount forkCount \
+         issues(states: OPEN) {{ totalCount }} pushedAt isArchived isFork \
+         defaultBranchRef {{ name target {{ ... on Commit {{ history(first: {}) {{ {} }} }} }} }} }} }} }} }}",
+        RATE_LIMIT_FIELDS, HISTORY_PAGE, HISTORY_FIELDS
+    )
+}
+
+/// Query of history pages of `count` repositories, aliased `repo0`, `repo1`, ...
+fn history_query(count: usize) -> String {
+    let parameters = (0..count)
+        .map(|index| format!("$owner{0}: String!, $name{0}: String!, $after{0}: String", index))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let repositories = (0..count)
+        .map(|index| {
+            format!(
+                "repo{0}: repository(owner: $owner{0}, name: $name{0}) {{ defaultBranchRef {{ \
+                 target {{ ... on Commit {{ history(first: {1}, after: $after{0}) {{ {2} }} }} }} }} }}",
+                index, HISTORY_PAGE, HISTORY_FIELDS
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("query({}) {{ {} {} }}", parameters, RATE_LIMIT_FIELDS, repositories)
+}
+
+/// Error reported in GraphQL response, e.g. `NOT_FOUND` repository.
+#[derive(Error, Debug)]
+#[error("{} {message} [{url}]", .kind.as_deref().unwrap_or("ERROR"))]
+pub(crate) struct GraphqlError {
+    /// Error type, e.g. `RATE_LIMITED`, `NOT_FOUND` or `FORBIDDEN`
+    kind: Option<String>,
+    message: String,
+    url: String,
+    /// Time of rate limit reset of `RATE_LIMITED` error, if known
+    reset: Option<i64>,
+    /// Pause before `RATE_LIMITED` query is re-issued
+    pause: Option<Duration>,
+}
+
+impl GraphqlError {
+    /// `RATE_LIMITED` query is re-issued after pause of limiter. Other errors are not transient.
+    pub(crate) fn retry(&self) -> Retry {
+        match self.pause {
+            Some(pause) if self.kind.as_deref() == Some(RATE_LIMITED) => Retry::RateLimited(pause),
+            _ => Retry::Never,
+        }
+    }
+}
+
+impl From<GraphqlError> for bus_factor::api::Error {
+    fn from(err: GraphqlError) -> Self {
+        let context = RequestContext::new(Some(err.url), None, err.message);
+        match err.kind.as_deref() {
+            Some(RATE_LIMITED) => bus_factor::api::Error::RateLimited {
+                reset: err.reset,
+                context,
+            },
+            Some("NOT_FOUND") => bus_factor::api::Error::NotFound(context),
+            Some("FORBIDDEN") => bus_factor::api::Error::Forbidden(context),
+            _ => bus_factor::api::Error::RequestError(context),
+        }
+    }
+}
+
+// Tests
+
+#[test]
+fn estimated_cost_test() {
+    assert_eq!(estimated_cost(1), 1);
+    assert_eq!(estimated_cost(101), 1);
+    assert_eq!(estimated_cost(250), 3);
+}
+
+#[test]
+fn cursor_after_test() {
+    let cursors = vec![Some("a".to_string()), None];
+    assert_eq!(cursor_after(&cursors, 0), Some(None));
+    assert_eq!(cursor_after(&cursors, 1), Some(Some("a".to_string())));
+    assert_eq!(
+        cursor_after(&cursors, 2),
+        None,
+        "There are no results after the last page"
+    );
+}
+
+#[test]
+fn history_query_test() {
+    let query = history_query(2);
+    assert!(query.starts_with(
+        "query($owner0: String!, $name0: String!, $after0: String, $owner1: String!, $name1: String!, $after1: String)"
+    ));
+    assert!(query.contains("repo0: repository(owner: $owner0, name: $name0)"));
+    assert!(query.contains("repo1: repository(owner: $owner1, name: $name1)"));
+    assert!(query.contains("history(first: 100, after: $after1)"));
+    assert_eq!(query.matches('{').count(), query.matches('}').count());
+    let query = search_query();
+    assert_eq!(query.matches('{').count(), query.matches('}').count());
+}
+
+#[test]
+fn graphql_error_test() {
+    let error = |kind: &str| GraphqlError {
+        kind: Some(kind.to_string()),
+        message: "message".to_string(),
+        url: "https://api.github.com/graphql".to_string(),
+        reset: Some(1000),
+        pause: Some(Duration::from_secs(1)),
+    };
+    use bus_factor::api::Error as ApiError;
+    assert!(matches!(
+        ApiError::from(error("RATE_LIMITED")),
+        ApiError::RateLimited { reset: Some(1000), .. }
+    ));
+    assert_eq!(
+        error("RATE_LIMITED").retry(),
+        Retry::RateLimited(Duration::from_secs(1))
+    );
+    assert_eq!(error("NOT_FOUND").retry(), Retry::Never);
+    assert!(matches!(ApiError::from(error("NOT_FOUND")), ApiError::NotFound(_)));
+    assert!(matches!(ApiError::from(error("FORBIDDEN")), ApiError::Forbidden(_)));
+    assert!(matches!(ApiError::from(error("INTERNAL")), ApiError::RequestError(_)));
+}
+
+#[tokio::test]
+async fn fetch_histories_dropped_test() {
+    use crate::limiter::RateLimit;
+    use bus_factor::clock::SystemClock;
+
+    // Server accepting connections, but never responding
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let inner = Arc::new(Inner {
+        client: Client::new(),
+        graphql_url: format!("http://{}/graphql", listener.local_addr().unwrap()),
+        limiter: RateLimiter::new(
+            Arc::new(Mutex::new(RateLimit::new(5000, 5000, 0))),
+            Arc::new(SystemClock),
+        ),
+        retry_policy: RetryPolicy::none(),
+    });
+    let (response, receiver) = oneshot::channel();
+    let request = HistoryRequest {
+        owner: "owner".to_string(),
+        name: "repo".to_string(),
+        after: None,
+        response,
+    };
+    let fetch = tokio::spawn(inner.fetch_histories(vec![request]));
+    drop(receiver);
+    let fetched = tokio::time::timeout(Duration::from_secs(5), fetch).await;
+    assert!(
+        fetched.is_ok(),
+        "Query should be cancelled once its requests are dropped"
+    );
+}
@@ -0,0 +1,181 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct Response<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<ErrorBody>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ErrorBody {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub message: String,
+    /// Path of failed field, starting with its (aliased) name
+    #[serde(default)]
+    pub path: Vec<serde_json::Value>,
+}
+
+/// Query data with rate limit of the query.
+pub trait RateLimitData {
+    fn rate_limit(&self) -> Option<&RateLimit>;
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    pub limit: u32,
+    pub cost: u32,
+    pub remaining: u32,
+    /// Time of reset in ISO 8601 format
+    pub reset_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchData {
+    pub rate_limit: Option<RateLimit>,
+    pub search: Search,
+}
+
+impl RateLimitData for SearchData {
+    fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Search {
+    pub page_info: PageInfo,
+    pub nodes: Vec<Option<Repository>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Repository {
+    pub name: String,
+    pub name_with_owner: String,
+    pub owner: Owner,
+    pub url: String,
+    pub stargazer_count: u32,
+    pub fork_count: u32,
+    pub issues: TotalCount,
+    pub pushed_at: Option<String>,
+    pub is_archived: bool,
+    pub is_fork: bool,
+    /// `None` for empty repository
+    pub default_branch_ref: Option<BranchRef>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Owner {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TotalCount {
+    pub total_count: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BranchRef {
+    #[serde(default)]
+    pub name: String,
+    pub target: Option<Target>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Target {
+    pub history: Option<History>,
+}
+
+/// Page of commit history.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct History {
+    pub page_info: PageInfo,
+    pub nodes: Vec<Commit>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub end_cursor: Option<String>,
+    pub has_next_page: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Commit {
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitAuthor {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// GitHub account of commit author, if any
+    pub user: Option<Owner>,
+}
+
+/// Histories of batched repositories, by their aliases.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryData {
+    pub rate_limit: Option<RateLimit>,
+    #[serde(flatten)]
+    pub repositories: HashMap<String, Option<HistoryRepository>>,
+}
+
+impl RateLimitData for HistoryData {
+    fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRepository {
+    pub default_branch_ref: Option<BranchRef>,
+}
+
+/// Empty repository has empty history. History not fetched with repository is `None`.
+impl From<Repository> for super::GithubGraphqlRepo {
+    fn from(repo: Repository) -> Self {
+        let (default_branch, history) = match repo.default_branch_ref {
+            Some(branch) => (branch.name, branch.target.and_then(|target| target.history)),
+            None => (String::new(), Some(History::default())),
+        };
+        super::GithubGraphqlRepo::new(
+            repo.name,
+            bus_factor::api::RepoDetails {
+                owner: repo.owner.login,
+                full_name: repo.name_with_owner,
+                html_url: repo.url,
+                stars: repo.stargazer_count,
+                forks: repo.fork_count,
+                open_issues: repo.issues.total_count,
+                default_branch,
+                pushed_at: repo.pushed_at,
+                archived: repo.is_archived,
+                fork: repo.is_fork,
+            },
+            history,
+        )
+    }
+}
+
+impl Commit {
+    /// Login of commit author, or (for `anonymous` authors without GitHub account) their email or name.
+    pub fn author_name(self, anonymous: bool) -> Option<String> {
+        let author = self.author?;
+        match author.user {
+            Some(user) => Some(user.login),
+            None if anonymous => author.email.filter(|email| !email.is_empty()).or(author.name),
+            None => None,
+        }
+    }
+}
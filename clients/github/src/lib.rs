@@ -1,19 +1,65 @@
 mod builder;
+mod graphql;
 mod limiter;
 mod payload;
 
 use async_trait::async_trait;
 use bus_factor::api::Contributor;
+use bus_factor::api::ContributorsPage;
+use bus_factor::api::RepoDetails;
+use bus_factor::api::RequestContext;
+use bus_factor::api::RequestKind;
+use bus_factor::api::ResourceStats;
 use bus_factor::api::Sort;
+use bus_factor::http::{retry_request_error, retry_status};
+use bus_factor::retry::{Retry, Retryable};
 use derive_more::Constructor;
 use limiter::RateLimiter;
+use log::info;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use reqwest::Response;
+use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
 use std::convert::AsRef;
+use std::str::FromStr;
+use std::time::Duration;
+use strum_macros::{AsRefStr, EnumString};
 use thiserror::Error;
+use tokio::sync::Mutex;
 
 pub use builder::GithubClientBuilder;
+pub use bus_factor::clock::{Clock, SimulatedClock, SystemClock};
+pub use bus_factor::retry::RetryPolicy;
+pub use graphql::{GithubGraphqlClient, GithubGraphqlRepo};
+pub use limiter::Pacing;
+
+/// Pause after secondary rate limit response without `Retry-After` header, as advised by GitHub docs.
+const DEFAULT_SECONDARY_RATE_LIMIT_PAUSE: u64 = 60;
+
+/// Contributors endpoint lists only this many contributors, following ones are returned as anonymous.
+const MAX_LISTED_CONTRIBUTORS: u32 = 500;
+
+/// Contributor statistics endpoint returns only this many top contributors.
+const MAX_STATS_CONTRIBUTORS: usize = 100;
+
+/// Number of polls of contributors while GitHub computes them (202 response).
+const POLL_ATTEMPTS: u32 = 5;
+
+/// Delay between polls of contributors.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// GitHub API used by client.
+#[derive(Debug, EnumString, Clone, Copy, AsRefStr, PartialEq, Eq)]
+pub enum GithubApi {
+    /// REST API (v3), see `GithubClient`
+    #[strum(serialize = "rest")]
+    Rest,
+    /// GraphQL API (v4), see `GithubGraphqlClient`
+    #[strum(serialize = "graphql")]
+    Graphql,
+}
 
 #[derive(Constructor)]
 pub struct GithubClient {
@@ -21,12 +67,16 @@ pub struct GithubClient {
     github_url: String,
     repos_limiter: RateLimiter,
     contrib_limiter: RateLimiter,
+    anonymous_contributors: bool,
+    retry_policy: RetryPolicy,
 }
 
 #[derive(Debug)]
 pub struct GithubRepo {
     name: String,
-    owner: String,
+    details: RepoDetails,
+    /// Contributors from statistics, fetched on first contributors request of repository too large to list them
+    contributor_stats: Mutex<Option<Vec<Contributor>>>,
 }
 
 impl bus_factor::api::Repo for GithubRepo {
@@ -34,6 +84,10 @@ impl bus_factor::api::Repo for GithubRepo {
     fn name(&self) -> Self::T {
         self.name.clone()
     }
+
+    fn details(&self) -> RepoDetails {
+        self.details.clone()
+    }
 }
 
 #[async_trait]
@@ -55,94 +109,476 @@ impl bus_factor::api::Client<GithubRepo, 100, 100, 1> for GithubClient {
         repo: &GithubRepo,
         page: u32,
         per_page: u32,
-    ) -> bus_factor::api::Result<Vec<Contributor>> {
+    ) -> bus_factor::api::Result<ContributorsPage> {
         self.get_top_contributors(repo, page, per_page)
             .await
             .map_err(crate::Error::into)
     }
+
+    async fn remaining_rate_limit(&self, kind: RequestKind) -> Option<f32> {
+        let limiter = match kind {
+            RequestKind::Repos => &self.repos_limiter,
+            RequestKind::Contributors => &self.contrib_limiter,
+        };
+        Some(limiter.remaining_ratio().await)
+    }
+
+    fn stats(&self) -> BTreeMap<String, ResourceStats> {
+        BTreeMap::from([
+            ("search".to_string(), self.repos_limiter.stats()),
+            ("core".to_string(), self.contrib_limiter.stats()),
+        ])
+    }
 }
 
 impl GithubClient {
     async fn get_top_repos(&self, lang: String, page: u32, per_page: u32, order: Sort) -> Result<Vec<GithubRepo>> {
         let request_url = format!("{}/search/repositories", self.github_url);
         let lang_query = format!("language:{}", lang);
-        self.repos_limiter.wait().await;
-        let response = self
-            .client
-            .get(request_url)
-            .query(&[
-                ("q", lang_query),
-                ("sort", "stars".to_string()),
-                ("order", "desc".to_string()),
-                ("page", page.to_string()),
-                ("per_page", per_page.to_string()),
-                ("sort", String::from(order.as_ref())),
-            ])
-            .send()
-            .await?;
-        self.repos_limiter.reset_limiter(&response.headers()).await?;
-        let response: payload::SearchRepos = read_response(response).await?;
+        let query = [
+            ("q", lang_query),
+            ("sort", "stars".to_string()),
+            ("order", "desc".to_string()),
+            ("page", page.to_string()),
+            ("per_page", per_page.to_string()),
+            ("sort", String::from(order.as_ref())),
+        ];
+        let response: payload::SearchRepos = self
+            .get(&self.repos_limiter, &request_url, &query)
+            .await?
+            .ok_or_else(|| format!("No content of {}", request_url))?;
         let response = response.items.into_iter().map(GithubRepo::from).collect();
         Ok(response)
     }
 
-    async fn get_top_contributors(&self, repo: &GithubRepo, page: u32, per_page: u32) -> Result<Vec<Contributor>> {
-        let request_url = format!("{}/repos/{}/{}/contributors", self.github_url, repo.owner, repo.name);
-        self.contrib_limiter.wait().await;
-        let response = self
-            .client
-            .get(request_url)
-            .query(&[
-                ("anon", false.to_string()), //TODO check if `true` will produce empty names
-                ("page", page.to_string()),
-                ("per_page", per_page.to_string()),
-            ])
-            .send()
-            .await?;
-        self.contrib_limiter.reset_limiter(response.headers()).await?;
-        let response: Vec<payload::Contributor> = read_response(response).await?;
-        let response = response.into_iter().map(Contributor::from).collect();
-        Ok(response)
+    /// Empty repository (204 response) has no contributors. Contributors not computed yet (202 response) are polled.
+    /// Repository with history too large to list contributors falls back to contributor statistics.
+    /// Page is truncated when it reaches the limit of listed contributors.
+    async fn get_top_contributors(&self, repo: &GithubRepo, page: u32, per_page: u32) -> Result<ContributorsPage> {
+        let request_url = format!(
+            "{}/repos/{}/{}/contributors",
+            self.github_url, repo.details.owner, repo.name
+        );
+        let query = [
+            ("anon", self.anonymous_contributors.to_string()),
+            ("page", page.to_string()),
+            ("per_page", per_page.to_string()),
+        ];
+        let response: Vec<payload::Contributor> = match self.poll(&self.contrib_limiter, &request_url, &query).await {
+            Ok(response) => response.unwrap_or_default(),
+            Err(Error::StatusError(error)) if error.is_too_large() => {
+                info!(
+                    "History of {} too large to list contributors. Falling back to contributor statistics.",
+                    repo.details.full_name
+                );
+                return self.get_contributor_stats(repo, page, per_page).await;
+            }
+            Err(error) => return Err(error),
+        };
+        let contributors = response.into_iter().map(Contributor::from).collect::<Vec<_>>();
+        // Further contributors are listed as anonymous, which are requested with `anon` param
+        let truncated = !self.anonymous_contributors
+            && contributors.len() as u32 == per_page
+            && page * per_page >= MAX_LISTED_CONTRIBUTORS;
+        Ok(ContributorsPage {
+            contributors,
+            truncated,
+        })
+    }
+
+    /// Contributors from statistics endpoint, which is not paginated, so they are fetched once per repository.
+    async fn get_contributor_stats(&self, repo: &GithubRepo, page: u32, per_page: u32) -> Result<ContributorsPage> {
+        let mut contributors = repo.contributor_stats.lock().await;
+        if contributors.is_none() {
+            let request_url = format!(
+                "{}/repos/{}/{}/stats/contributors",
+                self.github_url, repo.details.owner, repo.name
+            );
+            let stats: Vec<payload::ContributorStats> = self
+                .poll(&self.contrib_limiter, &request_url, &[])
+                .await?
+                .unwrap_or_default();
+            let mut stats = stats.into_iter().map(Contributor::from).collect::<Vec<_>>();
+            stats.sort_by_key(|contributor| std::cmp::Reverse(contributor.contributions));
+            *contributors = Some(stats);
+        }
+        let contributors = contributors.as_ref().expect("Contributor statistics fetched");
+        Ok(stats_page(contributors, page, per_page))
+    }
+
+    /// GET request polled until GitHub computes its result (202 response).
+    async fn poll<PAYLOAD: DeserializeOwned>(
+        &self,
+        limiter: &RateLimiter,
+        request_url: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<PAYLOAD>> {
+        for _ in 0..POLL_ATTEMPTS {
+            match self.get(limiter, request_url, query).await {
+                Err(Error::NotComputed(_)) => bus_factor::wait::sleep(POLL_INTERVAL).await,
+                result => return result,
+            }
+        }
+        Err(Error::NotComputed(request_url.to_string()))
+    }
+
+    /// GET request retried according to `RetryPolicy`.
+    /// Request hitting rate limit pauses `limiter` for the advised duration and is re-issued.
+    async fn get<PAYLOAD: DeserializeOwned>(
+        &self,
+        limiter: &RateLimiter,
+        request_url: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<PAYLOAD>> {
+        let description = format!("GET {}", request_url);
+        self.retry_policy
+            .retry(&description, true, || self.get_once(limiter, request_url, query))
+            .await
+    }
+
+    async fn get_once<PAYLOAD: DeserializeOwned>(
+        &self,
+        limiter: &RateLimiter,
+        request_url: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<PAYLOAD>> {
+        limiter.wait().await;
+        let response = self.client.get(request_url).query(query).send().await?;
+        // Error response status is more relevant than missing rate limit headers
+        let reset = limiter.reset_limiter(response.headers()).await;
+        let payload = match read_response(response).await {
+            Err(Error::StatusError(mut error)) => {
+                // Other requests sharing the limiter wait too
+                error.pause = error.rate_limit_pause(limiter.now());
+                if let Some(pause) = error.pause {
+                    limiter.pause(pause).await;
+                }
+                return Err(error.into());
+            }
+            payload => payload?,
+        };
+        reset?;
+        Ok(payload)
     }
 }
 
-async fn read_response<PAYLOAD: DeserializeOwned>(response: Response) -> reqwest::Result<PAYLOAD> {
-    let response = response.error_for_status()?;
-    response.json::<PAYLOAD>().await
+/// Returns `None` for response without content: 204 (e.g. empty repository),
+/// and `NotComputed` error for 202 (request accepted, but result is not computed yet).
+async fn read_response<PAYLOAD: DeserializeOwned>(response: Response) -> Result<Option<PAYLOAD>> {
+    match response.status() {
+        status if status.is_client_error() || status.is_server_error() => Err(StatusError::read(response).await.into()),
+        StatusCode::ACCEPTED => Err(Error::NotComputed(response.url().to_string())),
+        StatusCode::NO_CONTENT => Ok(None),
+        _ => Ok(Some(response.json::<PAYLOAD>().await?)),
+    }
+}
+
+/// Page of contributors sorted by their total number of commits.
+fn stats_page(contributors: &[Contributor], page: u32, per_page: u32) -> ContributorsPage {
+    let truncated = contributors.len() >= MAX_STATS_CONTRIBUTORS;
+    let skip = (page.saturating_sub(1) * per_page) as usize;
+    let contributors = contributors
+        .iter()
+        .skip(skip)
+        .take(per_page as usize)
+        .cloned()
+        .collect::<Vec<_>>();
+    let truncated = truncated && skip + per_page as usize >= MAX_STATS_CONTRIBUTORS;
+    ContributorsPage {
+        contributors,
+        truncated,
+    }
 }
 
 // Result and Errors
 
 pub(crate) type Result<T> = std::result::Result<T, crate::Error>;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub(crate) enum Error {
     #[error("Error: {0}")]
     Error(String),
     #[error("Request error: {}", .0.status().map_or_else(|| "Unknown".to_string(), |status| status.to_string()))] //meh
     RequestError(#[from] reqwest::Error),
+    #[error("Status error: {0}")]
+    StatusError(#[from] StatusError),
     #[error("Url parse error: {0}")]
     UrlParseError(#[from] url::ParseError),
     #[error("Header parse error: {0}")]
     HeaderParseError(#[from] reqwest::header::ToStrError),
     #[error("Header value parse error: {0}")]
     HeaderValueParseError(#[from] std::num::ParseIntError),
+    #[error("GraphQL error: {0}")]
+    GraphqlError(#[from] graphql::GraphqlError),
+    #[error("Result not computed yet [{0}]")]
+    NotComputed(String),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-impl Into<bus_factor::api::Error> for Error {
-    fn into(self) -> bus_factor::api::Error {
-        match self {
-            err @ Self::RequestError(_) => bus_factor::api::Error::RequestError(err.to_string()),
+/// Unsuccessful response.
+#[derive(Error, Debug)]
+#[error("{status} {message} [{url}]")]
+pub(crate) struct StatusError {
+    status: StatusCode,
+    url: String,
+    /// Remaining requests of primary rate limit, if known
+    remaining: Option<u32>,
+    /// Time of rate limit reset, if known
+    reset: Option<i64>,
+    /// Seconds to wait before re-issuing request, from `Retry-After` header
+    retry_after: Option<u64>,
+    /// Message from response body, if any
+    message: String,
+    /// Pause before rate limited request is re-issued, set by client which knows time of its limiter
+    pause: Option<Duration>,
+}
+
+impl StatusError {
+    async fn read(response: Response) -> Self {
+        let status = response.status();
+        let url = response.url().to_string();
+        let headers = response.headers();
+        let remaining = header_value::<u32>(headers, "x-ratelimit-remaining");
+        let reset = header_value::<i64>(headers, "x-ratelimit-reset");
+        let retry_after = header_value::<u64>(headers, "retry-after");
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<payload::ErrorBody>(&body)
+            .map(|body| body.message)
+            .unwrap_or(body);
+        StatusError {
+            status,
+            url,
+            remaining,
+            reset,
+            retry_after,
+            message,
+            pause: None,
+        }
+    }
+
+    /// Whether primary rate limit has been exhausted, or request hit secondary (abuse) rate limit.
+    fn is_rate_limited(&self) -> bool {
+        match self.status {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::FORBIDDEN => {
+                self.remaining == Some(0)
+                    || self.retry_after.is_some()
+                    || self.message.to_lowercase().contains("secondary rate limit")
+            }
+            _ => false,
+        }
+    }
+
+    /// Pause advised by rate limited response: `Retry-After` header, until reset of exhausted primary rate limit,
+    /// or default pause of secondary (abuse) rate limit. `None` if it is not such response.
+    fn rate_limit_pause(&self, now: i64) -> Option<Duration> {
+        if !self.is_rate_limited() {
+            return None;
+        }
+        let seconds = match (self.retry_after, self.reset) {
+            (Some(retry_after), _) => retry_after,
+            (None, Some(reset)) if self.remaining == Some(0) => (reset - now).max(0) as u64 + 1,
+            _ => DEFAULT_SECONDARY_RATE_LIMIT_PAUSE,
+        };
+        Some(Duration::from_secs(seconds))
+    }
+
+    /// Whether repository history is too large to list its contributors.
+    fn is_too_large(&self) -> bool {
+        self.status == StatusCode::FORBIDDEN && self.message.to_lowercase().contains("too large")
+    }
+}
+
+impl From<StatusError> for bus_factor::api::Error {
+    fn from(err: StatusError) -> Self {
+        let rate_limited = err.is_rate_limited();
+        let context = RequestContext::new(Some(err.url), Some(err.status.as_u16()), err.message);
+        match err.status {
+            _ if rate_limited => bus_factor::api::Error::RateLimited {
+                reset: err.reset,
+                context,
+            },
+            StatusCode::UNAUTHORIZED => bus_factor::api::Error::Unauthorized(context),
+            StatusCode::FORBIDDEN => bus_factor::api::Error::Forbidden(context),
+            StatusCode::NOT_FOUND => bus_factor::api::Error::NotFound(context),
+            status if status.is_server_error() => bus_factor::api::Error::ServerError(context),
+            _ => bus_factor::api::Error::RequestError(context),
+        }
+    }
+}
+
+impl From<Error> for bus_factor::api::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::StatusError(err) => err.into(),
+            Error::GraphqlError(err) => err.into(),
+            Error::RequestError(err) => {
+                let url = err.url().map(|url| url.to_string());
+                let status = err.status().map(|status| status.as_u16());
+                let context = RequestContext::new(url, status, err.to_string());
+                if err.is_timeout() {
+                    bus_factor::api::Error::Timeout(context)
+                } else if err.is_decode() {
+                    bus_factor::api::Error::Decode(context)
+                } else if let Some(status) = err.status() {
+                    StatusError {
+                        status,
+                        url: context.url.unwrap_or_default(),
+                        remaining: None,
+                        reset: None,
+                        retry_after: None,
+                        message: context.message,
+                        pause: None,
+                    }
+                    .into()
+                } else {
+                    bus_factor::api::Error::Transport(context)
+                }
+            }
             err => bus_factor::api::Error::Error(err.to_string()),
         }
     }
 }
 
+/// Transient errors are retried, and rate limited requests are re-issued after pause of their limiter.
+impl Retryable for Error {
+    fn retry(&self, idempotent: bool) -> Retry {
+        match self {
+            Error::StatusError(StatusError { pause: Some(pause), .. }) => Retry::RateLimited(*pause),
+            Error::StatusError(error) => retry_status(error.status, idempotent),
+            Error::GraphqlError(error) => error.retry(),
+            Error::RequestError(error) => retry_request_error(error, idempotent),
+            _ => Retry::Never,
+        }
+    }
+}
+
 //TODO do it using `thiserror`
 impl From<String> for Error {
     fn from(msg: String) -> Self {
         Error::Error(msg)
     }
 }
+
+fn header_value<T: FromStr>(headers: &HeaderMap, header: &str) -> Option<T> {
+    headers.get(header)?.to_str().ok()?.parse().ok()
+}
+
+// Tests
+
+#[cfg(test)]
+fn status_error(status: StatusCode, rate_limited: bool) -> StatusError {
+    StatusError {
+        status,
+        url: "https://api.github.com/repos/owner/repo/contributors".to_string(),
+        remaining: rate_limited.then_some(0),
+        reset: Some(1000),
+        retry_after: None,
+        message: "message".to_string(),
+        pause: None,
+    }
+}
+
+#[test]
+fn status_error_test() {
+    use bus_factor::api::Error as ApiError;
+    let error = ApiError::from(status_error(StatusCode::FORBIDDEN, true));
+    assert!(matches!(error, ApiError::RateLimited { reset: Some(1000), .. }));
+    assert_eq!(error.status(), Some(403));
+    assert_eq!(
+        error.context().and_then(|context| context.url.as_deref()),
+        Some("https://api.github.com/repos/owner/repo/contributors")
+    );
+    let error = ApiError::from(status_error(StatusCode::FORBIDDEN, false));
+    assert!(matches!(error, ApiError::Forbidden(_)));
+    let error = ApiError::from(status_error(StatusCode::UNAUTHORIZED, false));
+    assert!(matches!(error, ApiError::Unauthorized(_)));
+    let error = ApiError::from(status_error(StatusCode::NOT_FOUND, false));
+    assert!(matches!(error, ApiError::NotFound(_)));
+    let error = ApiError::from(status_error(StatusCode::BAD_GATEWAY, false));
+    assert!(matches!(error, ApiError::ServerError(_)));
+    let error = ApiError::from(status_error(StatusCode::UNPROCESSABLE_ENTITY, false));
+    assert!(matches!(error, ApiError::RequestError(_)));
+}
+
+#[test]
+fn retry_test() {
+    let retry = |status, idempotent| Error::StatusError(status_error(status, false)).retry(idempotent);
+    assert_eq!(retry(StatusCode::BAD_GATEWAY, false), Retry::Backoff);
+    assert_eq!(retry(StatusCode::INTERNAL_SERVER_ERROR, true), Retry::Backoff);
+    assert_eq!(retry(StatusCode::INTERNAL_SERVER_ERROR, false), Retry::Never);
+    assert_eq!(retry(StatusCode::NOT_FOUND, true), Retry::Never);
+    assert_eq!(Error::Error("error".to_string()).retry(true), Retry::Never);
+    let rate_limited = StatusError {
+        pause: Some(Duration::from_secs(11)),
+        ..status_error(StatusCode::FORBIDDEN, true)
+    };
+    assert_eq!(
+        Error::StatusError(rate_limited).retry(false),
+        Retry::RateLimited(Duration::from_secs(11))
+    );
+}
+
+#[test]
+fn rate_limit_pause_test() {
+    let now = 990;
+    let mut error = status_error(StatusCode::FORBIDDEN, false);
+    assert_eq!(error.rate_limit_pause(now), None);
+    error.message = "You have exceeded a secondary rate limit. Please wait a few minutes.".to_string();
+    assert_eq!(error.rate_limit_pause(now), Some(Duration::from_secs(60)));
+    error.retry_after = Some(30);
+    assert_eq!(error.rate_limit_pause(now), Some(Duration::from_secs(30)));
+    let mut error = status_error(StatusCode::TOO_MANY_REQUESTS, false);
+    assert_eq!(error.rate_limit_pause(now), Some(Duration::from_secs(60)));
+    error.retry_after = Some(5);
+    assert_eq!(error.rate_limit_pause(now), Some(Duration::from_secs(5)));
+    let mut error = status_error(StatusCode::SERVICE_UNAVAILABLE, false);
+    error.retry_after = Some(5);
+    assert_eq!(error.rate_limit_pause(now), None);
+
+    // Exhausted primary rate limit is paused until reset
+    let error = status_error(StatusCode::FORBIDDEN, true);
+    assert_eq!(error.rate_limit_pause(now), Some(Duration::from_secs(11)));
+    assert_eq!(error.rate_limit_pause(2000), Some(Duration::from_secs(1)));
+}
+
+#[test]
+fn too_large_test() {
+    let mut error = status_error(StatusCode::FORBIDDEN, false);
+    assert!(!error.is_too_large());
+    error.message =
+        "The history or contributor list is too large to list contributors for this repository via the API."
+            .to_string();
+    assert!(error.is_too_large());
+    error.status = StatusCode::UNPROCESSABLE_ENTITY;
+    assert!(!error.is_too_large());
+}
+
+#[test]
+fn stats_page_test() {
+    let stats = |count: u32| {
+        (0..count)
+            .rev()
+            .map(|i| Contributor {
+                name: format!("c{}", i),
+                contributions: i,
+                bot: false,
+            })
+            .collect::<Vec<_>>()
+    };
+    let page = stats_page(&stats(15), 1, 10);
+    assert_eq!(page.contributors.len(), 10);
+    assert_eq!(page.contributors[0].name, "c14");
+    assert!(!page.truncated);
+    let page = stats_page(&stats(15), 2, 10);
+    assert_eq!(page.contributors.len(), 5);
+    assert_eq!(page.contributors[4].name, "c0");
+
+    let page = stats_page(&stats(100), 1, 100);
+    assert_eq!(page.contributors.len(), 100);
+    assert!(page.truncated);
+    let page = stats_page(&stats(100), 1, 50);
+    assert!(!page.truncated);
+}
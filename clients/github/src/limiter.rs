@@ -1,64 +1,208 @@
 use crate::Result;
-use chrono::Utc;
-use derive_more::Constructor;
+use bus_factor::api::ResourceStats;
+use bus_factor::clock::Clock;
 use log::debug;
 use log::info;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderValue;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use strum_macros::{AsRefStr, EnumString};
 use tokio::sync::Mutex;
 
-#[derive(Constructor, Debug)]
+/// Pacing of requests within rate limit.
+#[derive(Debug, EnumString, Clone, Copy, AsRefStr, PartialEq, Eq)]
+pub enum Pacing {
+    /// Uses remaining requests as fast as possible, then waits for reset
+    #[strum(serialize = "burst")]
+    Burst,
+    /// Spreads remaining requests evenly over time until reset
+    #[strum(serialize = "smooth")]
+    Smooth,
+}
+
+#[derive(Debug)]
 pub struct RateLimit {
     limit: u32,
     remaining: u32,
     reset: i64,
+    /// Requests are paused until given time, e.g. after hitting secondary rate limit
+    paused_until: i64,
+    pacing: Pacing,
+    /// Ratio of `limit` left unused, e.g. for interactive use of the same token
+    reserve: f32,
+    /// Time in millis of next request in `Pacing::Smooth` mode
+    next_request: i64,
+}
+
+impl RateLimit {
+    pub fn new(limit: u32, remaining: u32, reset: i64) -> Self {
+        RateLimit {
+            limit,
+            remaining,
+            reset,
+            paused_until: 0,
+            pacing: Pacing::Burst,
+            reserve: 0.0,
+            next_request: 0,
+        }
+    }
+
+    pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    pub fn with_reserve(mut self, reserve: f32) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    #[cfg(test)]
+    pub(crate) fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    #[cfg(test)]
+    pub(crate) fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    #[cfg(test)]
+    pub(crate) fn reset(&self) -> i64 {
+        self.reset
+    }
+
+    /// Remaining requests available to limiter.
+    fn available(&self) -> u32 {
+        let reserved = (self.limit as f32 * self.reserve) as u32;
+        self.remaining.saturating_sub(reserved)
+    }
+
+    /// Time between requests spreading available requests evenly until reset.
+    fn interval(&self, now_millis: i64) -> i64 {
+        match self.available() {
+            0 => 0,
+            available => (self.reset * 1000 - now_millis).max(0) / available as i64,
+        }
+    }
 }
 
-#[derive(Constructor)]
 pub struct RateLimiter {
     limit: Arc<Mutex<RateLimit>>,
+    clock: Arc<dyn Clock>,
+    /// Number of requests let through
+    requests: AtomicU64,
+    /// Total time of waits, in milliseconds
+    waited_ms: AtomicU64,
 }
 
 impl RateLimiter {
+    pub fn new(limit: Arc<Mutex<RateLimit>>, clock: Arc<dyn Clock>) -> Self {
+        RateLimiter {
+            limit,
+            clock,
+            requests: AtomicU64::new(0),
+            waited_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Waits until request can be sent, and counts it.
     pub(crate) async fn wait(&self) {
-        while let Some(delay) = self.time_to_wait().await {
-            info!("Rate limiting wait: {} sec", delay.as_secs());
-            tokio::time::sleep(delay).await;
+        self.wait_for(1).await
+    }
+
+    /// Waits until request of given cost (in points of rate limit) can be sent, and counts it.
+    pub(crate) async fn wait_for(&self, cost: u32) {
+        while let Some(delay) = self.time_to_wait(cost).await {
+            match delay.as_secs() {
+                0 => debug!("Pacing wait: {} ms", delay.as_millis()),
+                secs => info!("Rate limiting wait: {} sec", secs),
+            }
+            bus_factor::wait::sleep(delay).await;
+            self.waited_ms.fetch_add(delay.as_millis() as u64, Ordering::Relaxed);
+        }
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> ResourceStats {
+        ResourceStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            rate_limit_wait_ms: self.waited_ms.load(Ordering::Relaxed),
         }
     }
 
-    async fn time_to_wait(&self) -> Option<Duration> {
+    /// Current time of limiter clock, in UTC epoch seconds.
+    pub(crate) fn now(&self) -> i64 {
+        self.clock.now()
+    }
+
+    /// Pauses requests for given duration, rounded up to full seconds.
+    pub(crate) async fn pause(&self, duration: Duration) {
+        let mut rate_limit = self.limit.lock().await;
+        let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+        let paused_until = self.clock.now() + seconds as i64;
+        rate_limit.paused_until = std::cmp::max(rate_limit.paused_until, paused_until);
+        debug!("Paused until {}", rate_limit.paused_until);
+    }
+
+    async fn time_to_wait(&self, cost: u32) -> Option<Duration> {
         let mut rate_limit = self.limit.lock().await;
-        if rate_limit.remaining > 0 {
+        // Request costing more than the whole limit is let through after reset
+        let cost = cost.clamp(1, rate_limit.limit.max(1));
+        let now_millis = self.clock.now_millis();
+        let now = now_millis.div_euclid(1000);
+        if rate_limit.paused_until > now {
+            return Some(Duration::new((rate_limit.paused_until - now) as u64, 0));
+        }
+        if rate_limit.pacing == Pacing::Smooth && rate_limit.available() >= cost && rate_limit.next_request > now_millis
+        {
+            return Some(Duration::from_millis((rate_limit.next_request - now_millis) as u64));
+        }
+        if rate_limit.available() >= cost {
             debug!("Remaining limit {}. Not waiting.", rate_limit.remaining);
-            rate_limit.remaining = rate_limit.remaining - 1;
+            rate_limit.next_request = now_millis + rate_limit.interval(now_millis) * cost as i64;
+            rate_limit.remaining -= cost;
             return None;
         }
-        let now = Utc::now().timestamp();
         if rate_limit.reset < now {
             debug!("Old reset. Resetting remaining to limit.");
             //TODO API limit could change so maybe should GET /rate_limit
-            rate_limit.remaining = rate_limit.limit - 1;
+            rate_limit.remaining = rate_limit.limit.saturating_sub(cost);
+            rate_limit.next_request = now_millis;
             return None;
         }
         Some(Duration::new(rate_limit.reset as u64 - now as u64 + 1, 0))
     }
 
+    /// Ratio of requests available to limiter to rate limit.
+    pub(crate) async fn remaining_ratio(&self) -> f32 {
+        let rate_limit = self.limit.lock().await;
+        match rate_limit.limit {
+            0 => 0.0,
+            limit => rate_limit.available() as f32 / limit as f32,
+        }
+    }
+
     pub(crate) async fn reset_limiter(&self, headers: &HeaderMap<HeaderValue>) -> crate::Result<()> {
+        let limit = read_header::<u32>(headers, "x-ratelimit-limit")?;
+        let remaining = read_header::<u32>(headers, "x-ratelimit-remaining")?;
+        let reset = read_header::<i64>(headers, "x-ratelimit-reset")?;
+        self.update(limit, remaining, reset).await;
+        Ok(())
+    }
+
+    /// Updates limit with values reported by API, e.g. in response headers.
+    pub(crate) async fn update(&self, limit: u32, remaining: u32, reset: i64) {
         let mut rate_limit = self.limit.lock().await;
-        rate_limit.limit = read_header::<u32>(headers, "x-ratelimit-limit")?;
+        rate_limit.limit = limit;
         // Min `remaining` because in case of parallel requests late response may arrive with old `remaining`
-        rate_limit.remaining = std::cmp::min(
-            read_header::<u32>(headers, "x-ratelimit-remaining")?,
-            rate_limit.remaining,
-        );
+        rate_limit.remaining = std::cmp::min(remaining, rate_limit.remaining);
         // Max `reset` because in case of parallel requests late response may arrive with old `reset`
-        rate_limit.reset = std::cmp::max(read_header::<i64>(headers, "x-ratelimit-reset")?, rate_limit.reset);
+        rate_limit.reset = std::cmp::max(reset, rate_limit.reset);
         debug!("Updated limits: {:?}", rate_limit);
-        Ok(())
     }
 }
 
@@ -69,31 +213,39 @@ where
 {
     let header = headers
         .get(header)
-        .ok_or_else(|| format!("Header {} not found", header.to_string()))
+        .ok_or_else(|| format!("Header {} not found", header))
         .map(HeaderValue::to_str)??;
     Ok(header.parse::<T>()?)
 }
 
-#[tokio::test]
+// Tests
+
+/// Limiter with clock starting at the beginning of a second.
+#[cfg(test)]
+fn test_limiter(limit: RateLimit) -> (RateLimiter, Arc<dyn Clock>) {
+    let clock: Arc<dyn Clock> = Arc::new(bus_factor::clock::SimulatedClock::new(1_600_000_000_000));
+    (RateLimiter::new(Arc::new(Mutex::new(limit)), clock.clone()), clock)
+}
+
+#[tokio::test(start_paused = true)]
 async fn wait_test() -> anyhow::Result<()> {
-    let reset = Utc::now().timestamp() + 1;
-    let limit = RateLimit::new(3, 1, reset);
-    let limiter = RateLimiter::new(Arc::new(Mutex::new(limit)));
+    let reset = 1_600_000_001;
+    let (limiter, clock) = test_limiter(RateLimit::new(3, 1, reset));
     limiter.wait().await;
     assert_eq!(
-        Utc::now().timestamp(),
+        clock.now(),
         reset - 1,
         "Limiter should not wait with remaining set to 1"
     );
 
     limiter.wait().await;
-    assert_eq!(Utc::now().timestamp(), reset + 1, "Limiter should wait 1s");
+    assert_eq!(clock.now(), reset + 1, "Limiter should wait 1s");
 
-    let then = Utc::now().timestamp();
+    let then = clock.now();
     limiter.wait().await;
     limiter.wait().await;
     assert_eq!(
-        Utc::now().timestamp(),
+        clock.now(),
         then,
         "Remaining should be reset after reaching limit, so no wait."
     );
@@ -104,11 +256,102 @@ async fn wait_test() -> anyhow::Result<()> {
     headers.insert("x-ratelimit-remaining", HeaderValue::from_str("2")?);
     headers.insert("x-ratelimit-reset", HeaderValue::from_str(&format!("{}", reset))?);
     limiter.reset_limiter(&headers).await?;
+    limiter.wait().await;
     assert_eq!(
-        reset,
-        Utc::now().timestamp() + 1,
+        clock.now(),
+        reset + 1,
         "Reset has been reset, but remaining arriving in header has been ignored, so limiter should wait."
     );
 
     Ok(())
 }
+
+#[tokio::test(start_paused = true)]
+async fn pause_test() {
+    let (limiter, clock) = test_limiter(RateLimit::new(10, 10, 0));
+    let then = clock.now();
+    limiter.pause(Duration::from_millis(1)).await;
+    limiter.wait().await;
+    assert_eq!(clock.now(), then + 1, "Limiter should wait until pause ends");
+    let stats = limiter.stats();
+    assert_eq!(stats.requests, 1);
+    assert!(stats.rate_limit_wait_ms > 0 && stats.rate_limit_wait_ms <= 1000);
+}
+
+#[tokio::test(start_paused = true)]
+async fn smooth_pacing_test() {
+    let reset = 1_600_000_010;
+    let (limiter, _) = test_limiter(RateLimit::new(10, 10, reset).with_pacing(Pacing::Smooth));
+    assert_eq!(limiter.time_to_wait(1).await, None, "First request should not wait");
+    assert_eq!(
+        limiter.time_to_wait(1).await,
+        Some(Duration::from_secs(1)),
+        "Remaining requests should be spread over time until reset"
+    );
+
+    let (limiter, _) = test_limiter(RateLimit::new(10, 10, reset));
+    assert_eq!(limiter.time_to_wait(1).await, None);
+    assert_eq!(limiter.time_to_wait(1).await, None, "Burst pacing should not wait");
+}
+
+#[tokio::test(start_paused = true)]
+async fn remaining_ratio_test() {
+    let (limiter, _) = test_limiter(RateLimit::new(10, 6, 1_600_000_010).with_reserve(0.1));
+    assert_eq!(limiter.remaining_ratio().await, 0.5);
+}
+
+#[tokio::test(start_paused = true)]
+async fn reserve_test() {
+    let reset = 1_600_000_010;
+    let (limiter, _) = test_limiter(RateLimit::new(10, 3, reset).with_reserve(0.2));
+    assert_eq!(limiter.time_to_wait(1).await, None);
+    assert_eq!(
+        limiter.time_to_wait(1).await,
+        Some(Duration::from_secs(11)),
+        "Reserved requests should not be used, so limiter should wait until reset"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn hour_long_pacing_test() {
+    let start = 1_600_000_000;
+    let reset = start + 3600;
+    let (limiter, clock) = test_limiter(RateLimit::new(5000, 5000, reset).with_pacing(Pacing::Smooth));
+    for _ in 0..5000 {
+        limiter.wait().await;
+    }
+    assert!(
+        clock.now() > reset - 10 && clock.now() <= reset,
+        "Requests should be spread over the whole hour, but finished at {}",
+        clock.now() - start
+    );
+    limiter.wait().await;
+    assert_eq!(
+        clock.now(),
+        reset + 1,
+        "Limiter should wait for reset after using all requests"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn cost_test() {
+    let reset = 1_600_000_010;
+    let (limiter, _) = test_limiter(RateLimit::new(10, 5, reset));
+    assert_eq!(limiter.time_to_wait(3).await, None);
+    assert_eq!(
+        limiter.time_to_wait(3).await,
+        Some(Duration::from_secs(11)),
+        "Request costing more than remaining points should wait until reset"
+    );
+    assert_eq!(limiter.time_to_wait(2).await, None);
+
+    let (limiter, clock) = test_limiter(RateLimit::new(10, 10, reset).with_pacing(Pacing::Smooth));
+    let then = clock.now();
+    limiter.wait_for(5).await;
+    limiter.wait_for(1).await;
+    assert_eq!(
+        clock.now(),
+        then + 5,
+        "Smooth pacing should wait for the interval of each point of previous request"
+    );
+}
@@ -9,6 +9,22 @@ pub struct SearchRepos {
 pub struct Repo {
     pub name: String,
     pub owner: RepoOwner,
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub stargazers_count: u32,
+    #[serde(default)]
+    pub forks_count: u32,
+    #[serde(default)]
+    pub open_issues_count: u32,
+    #[serde(default)]
+    pub default_branch: String,
+    pub pushed_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -18,28 +34,89 @@ pub struct RepoOwner {
 
 impl From<Repo> for crate::GithubRepo {
     fn from(repo: Repo) -> Self {
+        let full_name = repo
+            .full_name
+            .unwrap_or_else(|| format!("{}/{}", repo.owner.login, repo.name));
         crate::GithubRepo {
             name: repo.name,
-            owner: repo.owner.login,
+            details: bus_factor::api::RepoDetails {
+                owner: repo.owner.login,
+                full_name,
+                html_url: repo.html_url,
+                stars: repo.stargazers_count,
+                forks: repo.forks_count,
+                open_issues: repo.open_issues_count,
+                default_branch: repo.default_branch,
+                pushed_at: repo.pushed_at,
+                archived: repo.archived,
+                fork: repo.fork,
+            },
+            contributor_stats: tokio::sync::Mutex::new(None),
         }
     }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Contributor {
-    pub login: String,
+    /// Missing for anonymous contributors
+    pub login: Option<String>,
+    /// Present only for anonymous contributors
+    pub email: Option<String>,
+    /// Present only for anonymous contributors
+    pub name: Option<String>,
     pub contributions: u32,
+    /// Account type, e.g. `User`, `Bot` or `Anonymous`
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
 }
 
+/// Anonymous contributors are named by their email, so they can be merged with `ContributorAliases`.
 impl From<Contributor> for bus_factor::api::Contributor {
     fn from(contributor: Contributor) -> Self {
         bus_factor::api::Contributor {
-            name: contributor.login,
+            name: contributor
+                .login
+                .or(contributor.email)
+                .or(contributor.name)
+                .unwrap_or_default(),
             contributions: contributor.contributions,
+            bot: contributor.kind.as_deref() == Some("Bot"),
+        }
+    }
+}
+
+/// Contributor statistics, listing only top contributors.
+#[derive(Deserialize, Debug)]
+pub struct ContributorStats {
+    /// Missing for commits of unknown author
+    pub author: Option<StatsAuthor>,
+    /// Total number of commits
+    pub total: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StatsAuthor {
+    pub login: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+impl From<ContributorStats> for bus_factor::api::Contributor {
+    fn from(stats: ContributorStats) -> Self {
+        let bot = stats.author.as_ref().and_then(|author| author.kind.as_deref()) == Some("Bot");
+        bus_factor::api::Contributor {
+            name: stats.author.map(|author| author.login).unwrap_or_default(),
+            contributions: stats.total,
+            bot,
         }
     }
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ErrorBody {
+    pub message: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct RateLimitBody {
     pub resources: RateLimitResources,
@@ -49,6 +126,9 @@ pub struct RateLimitBody {
 pub struct RateLimitResources {
     pub core: RateLimitResource,
     pub search: RateLimitResource,
+    /// Points of GraphQL API, missing in some GitHub Enterprise versions
+    #[serde(default)]
+    pub graphql: Option<RateLimitResource>,
 }
 
 #[derive(Deserialize, Debug)]
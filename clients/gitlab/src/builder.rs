@@ -0,0 +1,94 @@
+use crate::limiter::RateLimiter;
+use crate::GitlabClient;
+use bus_factor::clock::{Clock, SystemClock};
+use bus_factor::retry::RetryPolicy;
+use reqwest::header;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use reqwest::ClientBuilder;
+use secrecy::ExposeSecret;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default timeout of a single request.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct GitlabClientBuilder {
+    client_builder: ClientBuilder,
+    gitlab_url: String,
+    headers: HeaderMap,
+    retry_policy: RetryPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for GitlabClientBuilder {
+    fn default() -> Self {
+        Self {
+            client_builder: ClientBuilder::default().timeout(DEFAULT_REQUEST_TIMEOUT),
+            gitlab_url: "https://gitlab.com/api/v4".to_string(),
+            headers: HeaderMap::from_iter([(header::USER_AGENT, HeaderValue::from_static("curl"))]),
+            retry_policy: RetryPolicy::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl GitlabClientBuilder {
+    /// Personal, project or group access token.
+    pub fn try_with_token(self, token: secrecy::SecretString) -> bus_factor::api::Result<GitlabClientBuilder> {
+        Ok(self.try_with_header(HeaderName::from_static("private-token"), token.expose_secret())?)
+    }
+
+    /// OAuth 2.0 access token.
+    pub fn try_with_oauth_token(self, token: secrecy::SecretString) -> bus_factor::api::Result<GitlabClientBuilder> {
+        let value = format!("Bearer {}", token.expose_secret());
+        Ok(self.try_with_header(header::AUTHORIZATION, value)?)
+    }
+
+    /// API URL, e.g. `https://gitlab.example.com/api/v4` of self-managed GitLab. `https://gitlab.com/api/v4` by default.
+    pub fn with_gitlab_url<STR: AsRef<str>>(mut self, url: STR) -> GitlabClientBuilder {
+        self.gitlab_url = url.as_ref().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Retry policy of requests failed with transient error or rate limit. `RetryPolicy::default()` by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> GitlabClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Clock of rate limiter. `SystemClock` by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> GitlabClientBuilder {
+        self.clock = clock;
+        self
+    }
+
+    /// Timeout of a single request. 30 sec by default.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> GitlabClientBuilder {
+        self.client_builder = self.client_builder.timeout(timeout);
+        self
+    }
+
+    fn try_with_header(mut self, key: HeaderName, val: impl AsRef<str>) -> anyhow::Result<GitlabClientBuilder> {
+        let mut val = HeaderValue::from_str(val.as_ref())?;
+        val.set_sensitive(key != header::USER_AGENT);
+        self.headers.insert(key, val);
+        Ok(self)
+    }
+
+    /// Rate limit is not requested upfront, but read from `RateLimit-*` headers of responses.
+    pub fn build(self) -> bus_factor::api::Result<GitlabClient> {
+        let client = self
+            .client_builder
+            .default_headers(self.headers)
+            .build()
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(GitlabClient {
+            client,
+            gitlab_url: self.gitlab_url,
+            limiter: RateLimiter::new(self.clock),
+            retry_policy: self.retry_policy,
+        })
+    }
+}
@@ -0,0 +1,159 @@
+mod builder;
+mod limiter;
+mod payload;
+
+use async_trait::async_trait;
+use bus_factor::api::Contributor;
+use bus_factor::api::ContributorsPage;
+use bus_factor::api::RepoDetails;
+use bus_factor::api::RequestKind;
+use bus_factor::api::ResourceStats;
+use bus_factor::api::Sort;
+use bus_factor::http::{read_response, Error, Result};
+use bus_factor::retry::RetryPolicy;
+use limiter::{RateLimit, RateLimiter};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+pub use builder::GitlabClientBuilder;
+
+/// Client of GitLab REST API (v4). Projects are searched by programming language,
+/// and contributors are listed by the repository contributors endpoint.
+pub struct GitlabClient {
+    client: Client,
+    gitlab_url: String,
+    limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+}
+
+#[derive(Debug)]
+pub struct GitlabRepo {
+    id: u64,
+    name: String,
+    /// Repository without default branch has no commits
+    empty: bool,
+    details: RepoDetails,
+}
+
+impl bus_factor::api::Repo for GitlabRepo {
+    type T = String;
+    fn name(&self) -> Self::T {
+        self.name.clone()
+    }
+
+    fn details(&self) -> RepoDetails {
+        self.details.clone()
+    }
+}
+
+#[async_trait]
+impl bus_factor::api::Client<GitlabRepo, 100, 100, 1> for GitlabClient {
+    async fn top_repos(
+        &self,
+        lang: String,
+        page: u32,
+        per_page: u32,
+        order: Sort,
+    ) -> bus_factor::api::Result<Vec<GitlabRepo>> {
+        self.get_top_repos(lang, page, per_page, order)
+            .await
+            .map_err(Error::into)
+    }
+
+    async fn top_contributors(
+        &self,
+        repo: &GitlabRepo,
+        page: u32,
+        per_page: u32,
+    ) -> bus_factor::api::Result<ContributorsPage> {
+        self.get_top_contributors(repo, page, per_page)
+            .await
+            .map_err(Error::into)
+    }
+
+    async fn remaining_rate_limit(&self, _kind: RequestKind) -> Option<f32> {
+        self.limiter.remaining_ratio().await
+    }
+
+    fn stats(&self) -> BTreeMap<String, ResourceStats> {
+        BTreeMap::from([("gitlab".to_string(), self.limiter.stats())])
+    }
+}
+
+impl GitlabClient {
+    /// Projects are ordered by stars, or by last activity. GitLab does not order projects by forks or issues.
+    async fn get_top_repos(&self, lang: String, page: u32, per_page: u32, order: Sort) -> Result<Vec<GitlabRepo>> {
+        let order_by = match order {
+            Sort::Stars => "star_count",
+            Sort::Updated => "last_activity_at",
+            order => return Err(format!("GitLab projects cannot be sorted by {}", order.as_ref()).into()),
+        };
+        let request_url = format!("{}/projects", self.gitlab_url);
+        let query = [
+            ("with_programming_language", lang),
+            ("order_by", order_by.to_string()),
+            ("sort", "desc".to_string()),
+            ("simple", "false".to_string()),
+            ("page", page.to_string()),
+            ("per_page", per_page.to_string()),
+        ];
+        let projects: Vec<payload::Project> = self.get(&request_url, &query).await?;
+        Ok(projects.into_iter().map(GitlabRepo::from).collect())
+    }
+
+    /// Empty repository has no contributors.
+    async fn get_top_contributors(&self, repo: &GitlabRepo, page: u32, per_page: u32) -> Result<ContributorsPage> {
+        if repo.empty {
+            return Ok(Vec::new().into());
+        }
+        let request_url = format!("{}/projects/{}/repository/contributors", self.gitlab_url, repo.id);
+        let query = [
+            ("order_by", "commits".to_string()),
+            ("sort", "desc".to_string()),
+            ("page", page.to_string()),
+            ("per_page", per_page.to_string()),
+        ];
+        let contributors: Vec<payload::Contributor> = self.get(&request_url, &query).await?;
+        Ok(contributors
+            .into_iter()
+            .map(Contributor::from)
+            .collect::<Vec<_>>()
+            .into())
+    }
+
+    /// GET request retried according to `RetryPolicy`, and after hitting rate limit once it resets.
+    async fn get<PAYLOAD: DeserializeOwned>(&self, request_url: &str, query: &[(&str, String)]) -> Result<PAYLOAD> {
+        let description = format!("GET {}", request_url);
+        self.retry_policy
+            .retry(&description, true, || self.get_once(request_url, query))
+            .await
+    }
+
+    async fn get_once<PAYLOAD: DeserializeOwned>(
+        &self,
+        request_url: &str,
+        query: &[(&str, String)],
+    ) -> Result<PAYLOAD> {
+        self.limiter.wait().await;
+        let response = self.client.get(request_url).query(query).send().await?;
+        if let Some(rate_limit) = RateLimit::read(response.headers()) {
+            self.limiter.update(rate_limit).await;
+        }
+        read_response(response, payload::error_message).await
+    }
+}
+
+// Tests
+
+#[test]
+fn contributor_test() {
+    let contributor = Contributor::from(payload::Contributor {
+        name: "Jane Doe".to_string(),
+        email: "jane@example.com".to_string(),
+        commits: 7,
+    });
+    assert_eq!(contributor.name, "jane@example.com");
+    assert_eq!(contributor.contributions, 7);
+    assert!(!contributor.bot);
+}
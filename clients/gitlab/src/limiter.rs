@@ -0,0 +1,191 @@
+use bus_factor::api::ResourceStats;
+use bus_factor::clock::Clock;
+use bus_factor::http::header_value;
+use log::{debug, info};
+use reqwest::header::HeaderMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Rate limit reported in `RateLimit-*` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Unix time in seconds
+    pub reset: i64,
+}
+
+impl RateLimit {
+    /// `None` if response has no rate limit headers, e.g. on self-managed instance without rate limits.
+    pub(crate) fn read(headers: &HeaderMap) -> Option<Self> {
+        Some(RateLimit {
+            limit: header_value(headers, "ratelimit-limit")?,
+            remaining: header_value(headers, "ratelimit-remaining")?,
+            reset: header_value(headers, "ratelimit-reset")?,
+        })
+    }
+}
+
+/// Limiter following rate limit of the last response. Requests are not limited until rate limit is known.
+pub(crate) struct RateLimiter {
+    limit: Mutex<Option<RateLimit>>,
+    clock: Arc<dyn Clock>,
+    /// Number of requests let through
+    requests: AtomicU64,
+    /// Total time of waits, in milliseconds
+    waited_ms: AtomicU64,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        RateLimiter {
+            limit: Mutex::new(None),
+            clock,
+            requests: AtomicU64::new(0),
+            waited_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Waits until request can be sent, and counts it.
+    pub(crate) async fn wait(&self) {
+        while let Some(delay) = self.time_to_wait(self.clock.now()).await {
+            info!("Rate limiting wait: {} sec", delay.as_secs());
+            bus_factor::wait::sleep(delay).await;
+            self.waited_ms.fetch_add(delay.as_millis() as u64, Ordering::Relaxed);
+        }
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    async fn time_to_wait(&self, now: i64) -> Option<Duration> {
+        let mut limit = self.limit.lock().await;
+        let limit = limit.as_mut()?;
+        if limit.remaining > 0 {
+            limit.remaining -= 1;
+            return None;
+        }
+        if limit.reset < now {
+            debug!("Old reset. Resetting remaining to limit.");
+            limit.remaining = limit.limit.saturating_sub(1);
+            return None;
+        }
+        Some(Duration::from_secs((limit.reset - now) as u64 + 1))
+    }
+
+    /// Updates limit with rate limit of response.
+    pub(crate) async fn update(&self, rate_limit: RateLimit) {
+        let mut limit = self.limit.lock().await;
+        let updated = match *limit {
+            // In case of parallel requests late response may arrive with old `remaining` and `reset`
+            Some(limit) if limit.reset >= rate_limit.reset => RateLimit {
+                remaining: std::cmp::min(limit.remaining, rate_limit.remaining),
+                ..limit
+            },
+            _ => rate_limit,
+        };
+        debug!("Updated limits: {:?}", updated);
+        *limit = Some(updated);
+    }
+
+    /// Ratio of remaining requests to rate limit, if known.
+    pub(crate) async fn remaining_ratio(&self) -> Option<f32> {
+        let limit = (*self.limit.lock().await)?;
+        match limit.limit {
+            0 => Some(0.0),
+            max => Some(limit.remaining as f32 / max as f32),
+        }
+    }
+
+    pub(crate) fn stats(&self) -> ResourceStats {
+        ResourceStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            rate_limit_wait_ms: self.waited_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Tests
+
+/// Limiter with clock starting at 1000 sec.
+#[cfg(test)]
+fn test_limiter() -> RateLimiter {
+    RateLimiter::new(Arc::new(bus_factor::clock::SimulatedClock::new(1_000_000)))
+}
+
+#[tokio::test]
+async fn time_to_wait_test() {
+    let limiter = test_limiter();
+    assert_eq!(limiter.time_to_wait(1000).await, None, "Unknown limit should not wait");
+    limiter
+        .update(RateLimit {
+            limit: 10,
+            remaining: 1,
+            reset: 1010,
+        })
+        .await;
+    assert_eq!(limiter.time_to_wait(1000).await, None);
+    assert_eq!(limiter.time_to_wait(1000).await, Some(Duration::from_secs(11)));
+    assert_eq!(
+        limiter.time_to_wait(1011).await,
+        None,
+        "Remaining should be reset to limit after reset"
+    );
+    assert_eq!(limiter.remaining_ratio().await, Some(0.9));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_test() {
+    let limiter = test_limiter();
+    limiter
+        .update(RateLimit {
+            limit: 10,
+            remaining: 0,
+            reset: 1010,
+        })
+        .await;
+    let started = tokio::time::Instant::now();
+    limiter.wait().await;
+    assert_eq!(
+        started.elapsed(),
+        Duration::from_secs(11),
+        "Limiter should wait until reset"
+    );
+    assert_eq!(limiter.stats().rate_limit_wait_ms, 11_000);
+}
+
+#[tokio::test]
+async fn update_test() {
+    let limiter = test_limiter();
+    let limit = |remaining, reset| RateLimit {
+        limit: 10,
+        remaining,
+        reset,
+    };
+    limiter.update(limit(5, 1010)).await;
+    limiter.update(limit(8, 1010)).await;
+    assert_eq!(
+        limiter.remaining_ratio().await,
+        Some(0.5),
+        "Late response with old remaining should be ignored"
+    );
+    limiter.update(limit(8, 1070)).await;
+    assert_eq!(limiter.remaining_ratio().await, Some(0.8), "New window should be taken");
+}
+
+#[test]
+fn read_test() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(RateLimit::read(&headers), None);
+    headers.insert("RateLimit-Limit", "2000".parse().unwrap());
+    headers.insert("RateLimit-Remaining", "1999".parse().unwrap());
+    headers.insert("RateLimit-Reset", "1600000060".parse().unwrap());
+    assert_eq!(
+        RateLimit::read(&headers),
+        Some(RateLimit {
+            limit: 2000,
+            remaining: 1999,
+            reset: 1_600_000_060
+        })
+    );
+}
@@ -0,0 +1,87 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Project {
+    pub id: u64,
+    pub path: String,
+    pub path_with_namespace: String,
+    pub namespace: Namespace,
+    #[serde(default)]
+    pub web_url: String,
+    #[serde(default)]
+    pub star_count: u32,
+    #[serde(default)]
+    pub forks_count: u32,
+    /// Missing when issues are disabled
+    #[serde(default)]
+    pub open_issues_count: u32,
+    /// Missing for empty repository
+    pub default_branch: Option<String>,
+    pub last_activity_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    pub forked_from_project: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Namespace {
+    pub full_path: String,
+}
+
+impl From<Project> for crate::GitlabRepo {
+    fn from(project: Project) -> Self {
+        crate::GitlabRepo {
+            id: project.id,
+            empty: project.default_branch.is_none(),
+            details: bus_factor::api::RepoDetails {
+                owner: project.namespace.full_path,
+                full_name: project.path_with_namespace,
+                html_url: project.web_url,
+                stars: project.star_count,
+                forks: project.forks_count,
+                open_issues: project.open_issues_count,
+                default_branch: project.default_branch.unwrap_or_default(),
+                pushed_at: project.last_activity_at,
+                archived: project.archived,
+                fork: project.forked_from_project.is_some(),
+            },
+            name: project.path,
+        }
+    }
+}
+
+/// Contributor of repository, identified by commit email.
+#[derive(Deserialize, Debug)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: u32,
+}
+
+/// Contributors are named by their commit email, so they can be merged with `ContributorAliases`.
+impl From<Contributor> for bus_factor::api::Contributor {
+    fn from(contributor: Contributor) -> Self {
+        bus_factor::api::Contributor {
+            bot: contributor.name.ends_with("[bot]"),
+            name: match contributor.email.is_empty() {
+                true => contributor.name,
+                false => contributor.email,
+            },
+            contributions: contributor.commits,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ErrorBody {
+    #[serde(alias = "error")]
+    pub message: serde_json::Value,
+}
+
+/// Message of error response, which is either a string or an object of messages by field.
+pub fn error_message(body: &str) -> Option<String> {
+    match serde_json::from_str::<ErrorBody>(body).ok()?.message {
+        serde_json::Value::String(message) => Some(message),
+        message => Some(message.to_string()),
+    }
+}
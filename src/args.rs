@@ -1,8 +1,15 @@
+use crate::forge::Forge;
+use crate::output::OutputFormat;
 use bus_factor::api::Sort;
+use bus_factor::calculator::ContributorsWindow;
+use bus_factor::metric::Metric;
+use chrono::NaiveDate;
 use clap::Parser;
+use github_client::{GithubApi, Pacing};
 use secrecy::SecretString;
 use std::{
     fmt::{Debug, Display},
+    path::PathBuf,
     str::FromStr,
 };
 
@@ -17,21 +24,164 @@ pub struct Args {
     #[clap(short, long, env)]
     pub project_count: u32,
 
-    #[clap(short, long, env, default_value = "stars")]
-    pub sort: Sort,
+    /// Repositories order (stars, forks, help_wanted_issues or udpated), supported orders depend on forge
+    /// [default: stars, or udpated for bitbucket and local]
+    #[clap(short, long, env)]
+    pub sort: Option<Sort>,
+
+    /// Repository service (github, gitlab, gitea, forgejo, bitbucket, bitbucket-data-center or local)
+    #[clap(long, env, default_value = "github")]
+    pub forge: Forge,
 
-    /// API OAuth access token
+    /// API access token
     #[clap(short, long, env)]
     pub api_token: Option<SecretString>,
 
-    /// Repository API URL
-    #[clap(long, env, default_value = "https://api.github.com")]
-    pub api_url: String,
+    /// API token is an OAuth access token (GitLab), instead of personal access token
+    #[clap(long)]
+    pub oauth_token: bool,
+
+    /// Username of account whose app password is the API token (Bitbucket). Without it, token is sent as Bearer token
+    #[clap(long, env)]
+    pub api_username: Option<String>,
+
+    /// Workspace (Bitbucket) or project key (Bitbucket Data Center) of analyzed repositories
+    #[clap(long, env)]
+    pub workspace: Option<String>,
+
+    /// Directory of cloned git repositories, or a single repository (local forge)
+    #[clap(long, env)]
+    pub repos_dir: Option<PathBuf>,
+
+    /// Branch whose history is counted, instead of `HEAD` (local forge)
+    #[clap(long, env)]
+    pub branch: Option<String>,
+
+    /// Count commits committed on this day (YYYY-MM-DD) or later (local forge)
+    #[clap(long, env)]
+    pub since: Option<NaiveDate>,
+
+    /// Count commits committed on this day (YYYY-MM-DD) or earlier (local forge)
+    #[clap(long, env)]
+    pub until: Option<NaiveDate>,
+
+    /// Repository API URL, e.g. `https://api.github.com` (default of `github` forge),
+    /// `https://gitlab.com/api/v4` (default of `gitlab` forge), `https://codeberg.org/api/v1` (default of `gitea` forge),
+    /// `https://api.bitbucket.org/2.0` (default of `bitbucket` forge), or `https://bitbucket.example.com/rest/api/1.0`
+    #[clap(long, env)]
+    pub api_url: Option<String>,
+
+    /// GitHub API (rest, graphql). GraphQL counts contributors from commit history, fetched in batched queries
+    #[clap(long, env, default_value = "rest")]
+    pub github_api: GithubApi,
+
+    /// Number of latest commits of repository counted when contributors are derived from history (GitHub GraphQL, Gitea, Bitbucket, local)
+    #[clap(long, env, default_value_t = 1000)]
+    pub max_history_commits: u32,
 
-    /// Bus factor threshold
+    /// Contributions concentration metric (top-share, gini, entropy, hhi, truck-factor)
+    #[clap(long, env, default_value = "truck-factor")]
+    pub metric: Metric,
+
+    /// Bus factor threshold of metric score
     #[clap(short, long, env, default_value_t = 0.75, parse(try_from_str=threshold_in_range))]
     pub threshold: f32,
 
+    /// Maximal number of key contributors (bus factor) for repository to be reported
+    #[clap(short, long, env, default_value_t = 1, parse(try_from_str=max_factor_in_range))]
+    pub max_factor: u32,
+
+    /// Contributors taken into account: number of top contributors, `all`, or contributions saturation
+    /// (e.g. `90%` stops paging once a page makes at most 10% of contributions fetched so far)
+    #[clap(long, env, default_value = "25")]
+    pub contributors_window: ContributorsWindow,
+
+    /// Fetch all contributors pages within contributors window, even if bus factor result is already known
+    #[clap(long)]
+    pub no_early_exit: bool,
+
+    /// Report every analyzed repository with its score, not only those with a bus factor (implies `--no-early-exit`)
+    #[clap(long)]
+    pub full_report: bool,
+
+    /// Regex of contributor logins excluded from calculation (e.g. `-bot$`). Can be repeated
+    #[clap(long, multiple_occurrences(true))]
+    pub exclude: Vec<String>,
+
+    /// File with regexes of contributor logins excluded from calculation, one per line (`#` starts a comment)
+    #[clap(long, env)]
+    pub exclude_file: Option<PathBuf>,
+
+    /// Do not exclude built-in deny list of bot accounts (e.g. `dependabot[bot]`, `bors`) and `Bot` type accounts
+    #[clap(long)]
+    pub include_bots: bool,
+
+    /// `.mailmap` file, or TOML file with `aliases` table, mapping logins and emails of the same contributor
+    #[clap(long, env)]
+    pub aliases_file: Option<PathBuf>,
+
+    /// Count contributors without account, identified by their commit email
+    #[clap(long)]
+    pub anonymous: bool,
+
+    /// Report contributors excluded from calculation
+    #[clap(short, long)]
+    pub verbose: bool,
+
+    /// Output format (text, json, ndjson, csv, markdown)
+    #[clap(long, env, default_value = "text")]
+    pub output_format: OutputFormat,
+
+    /// Output file. Standard output if not set
+    #[clap(long, env)]
+    pub output_file: Option<PathBuf>,
+
+    /// Include run summary in `json` or `ndjson` output, instead of printing it to standard error
+    #[clap(long)]
+    pub summary_in_output: bool,
+
+    /// Maximal ratio of repositories which failed to be analyzed, above which program exits with error
+    #[clap(long, env, default_value_t = 0.1, parse(try_from_str=failure_ratio_in_range))]
+    pub max_failure_ratio: f32,
+
+    /// Maximal number of attempts of a request failed with transient error (e.g. 502, 503, connection reset)
+    #[clap(long, env, default_value_t = 3, parse(try_from_str=max_attempts_in_range))]
+    pub max_attempts: u32,
+
+    /// Backoff of first retry in milliseconds, doubled with every next one (with random jitter)
+    #[clap(long, env, default_value_t = 500)]
+    pub retry_backoff_ms: u64,
+
+    /// Maximal backoff of retry in milliseconds
+    #[clap(long, env, default_value_t = 30000)]
+    pub max_retry_backoff_ms: u64,
+
+    /// Pacing of requests within rate limits (GitHub): `smooth` spreads them evenly until reset, `burst` sends them
+    /// as fast as possible and then waits for reset
+    #[clap(long, env, default_value = "burst")]
+    pub pacing: Pacing,
+
+    /// Ratio of rate limits left unused, e.g. for interactive use of the same API token (GitHub)
+    #[clap(long, env, default_value_t = 0.0, parse(try_from_str=rate_limit_reserve_in_range))]
+    pub rate_limit_reserve: f32,
+
+    /// Adjust number of parallel requests to their latency, failures and remaining rate limit,
+    /// up to `max_repo_req` and `max_contrib_req`
+    #[clap(long)]
+    pub adaptive_concurrency: bool,
+
+    /// Output results as soon as they are calculated, instead of in search order (see `rank` of results)
+    #[clap(long)]
+    pub unordered: bool,
+
+    /// Timeout of a single request in seconds
+    #[clap(long, env, default_value_t = 30)]
+    pub request_timeout_secs: u64,
+
+    /// Time limit of the whole run in seconds, after which partial results are reported and program exits with code 2
+    #[clap(long, env)]
+    pub deadline_secs: Option<u64>,
+
     /// Maximal parallel repository search requests
     #[clap(long, env, default_value_t = 1, parse(try_from_str=max_repo_req_in_range))]
     pub max_repo_req: u32,
@@ -46,6 +196,22 @@ fn threshold_in_range(value: &str) -> clap::Result<f32, String> {
     number_in_range(value, 0.0, 1.0, "threshold".to_string())
 }
 
+fn failure_ratio_in_range(value: &str) -> clap::Result<f32, String> {
+    number_in_range(value, 0.0, 1.0, "max_failure_ratio".to_string())
+}
+
+fn rate_limit_reserve_in_range(value: &str) -> clap::Result<f32, String> {
+    number_in_range(value, 0.0, 1.0, "rate_limit_reserve".to_string())
+}
+
+fn max_factor_in_range(value: &str) -> clap::Result<u32, String> {
+    number_in_range(value, 1, u32::MAX, "max_factor".to_string())
+}
+
+fn max_attempts_in_range(value: &str) -> clap::Result<u32, String> {
+    number_in_range(value, 1, u32::MAX, "max_attempts".to_string())
+}
+
 fn max_repo_req_in_range(value: &str) -> clap::Result<u32, String> {
     //TODO min == 0.0 makes no sense but wanted to reuse method...
     number_in_range(value, 1, u32::MAX, "max_repo_req".to_string())
@@ -0,0 +1,38 @@
+use bus_factor::api::Sort;
+use strum_macros::{AsRefStr, EnumString};
+
+/// Repository service hosting analyzed repositories.
+#[derive(Debug, EnumString, Clone, Copy, AsRefStr, PartialEq, Eq)]
+pub enum Forge {
+    /// GitHub or GitHub Enterprise
+    #[strum(serialize = "github")]
+    Github,
+    /// gitlab.com or self-managed GitLab
+    #[strum(serialize = "gitlab")]
+    Gitlab,
+    /// Gitea or Forgejo instance, e.g. Codeberg
+    #[strum(serialize = "gitea", serialize = "forgejo")]
+    Gitea,
+    /// Bitbucket Cloud
+    #[strum(serialize = "bitbucket")]
+    Bitbucket,
+    /// Self-hosted Bitbucket Data Center or Bitbucket Server
+    #[strum(serialize = "bitbucket-data-center", serialize = "bitbucket-server")]
+    BitbucketDataCenter,
+    /// Git repositories cloned to local directory, analyzed offline
+    #[strum(serialize = "local", serialize = "git")]
+    Local,
+}
+
+impl Forge {
+    /// Orders repositories can be sorted by, the default one first. Bitbucket Data Center does not sort repositories.
+    pub fn sorts(&self) -> &'static [Sort] {
+        match self {
+            Forge::Github => &[Sort::Stars, Sort::Forks, Sort::HelpWantedIssues, Sort::Updated],
+            Forge::Gitlab => &[Sort::Stars, Sort::Updated],
+            Forge::Gitea => &[Sort::Stars, Sort::Forks, Sort::Updated],
+            Forge::Bitbucket | Forge::Local => &[Sort::Updated],
+            Forge::BitbucketDataCenter => &[],
+        }
+    }
+}
@@ -1,26 +1,240 @@
 //TODO it is pub only for functional test...
 pub mod args;
+pub mod forge;
+pub mod output;
 
 use args::Args;
-use bus_factor::api::Result;
-use bus_factor::{BusFactorCalculator, BusFactorStream};
-use github_client::GithubClientBuilder;
+use bitbucket_client::{BitbucketClient, BitbucketClientBuilder};
+use bus_factor::api::{Client, Error, Repo, Result, Sort};
+use bus_factor::metric::{Entropy, Gini, Herfindahl, Metric, TopShare, TruckFactor};
+use bus_factor::retry::RetryPolicy;
+use bus_factor::{BusFactorCalculator, BusFactorStream, ContributorAliases, ContributorFilter, RunStats};
+use forge::Forge;
+use git_client::{LocalGitClient, LocalGitClientBuilder};
+use gitea_client::{GiteaClient, GiteaClientBuilder};
+use github_client::{GithubApi, GithubClientBuilder, Pacing};
+use gitlab_client::{GitlabClient, GitlabClientBuilder};
+use std::path::Path;
+use std::time::Duration;
 
-pub async fn calculate_bus_factor(args: Args) -> Result<BusFactorStream> {
-    env_logger::init();
+/// Returns stream of results, and statistics of the run updated while results are consumed.
+pub async fn calculate_bus_factor(args: Args) -> Result<(BusFactorStream, RunStats)> {
+    // Logger might have been initialized by previous calculation
+    let _ = env_logger::try_init();
+    check_forge_args(&args)?;
 
-    let mut client_builder = GithubClientBuilder::default().with_github_url(args.api_url);
-    if let Some(token) = args.api_token {
-        client_builder = client_builder.try_with_token(token)?; //TODO ideally in builder the only `try_` method should be .build()
+    match args.forge {
+        Forge::Github => {
+            let client_builder = github_client_builder(&args)?;
+            match args.github_api {
+                GithubApi::Rest => calculate(client_builder.build().await?, args),
+                GithubApi::Graphql => calculate(client_builder.build_graphql().await?, args),
+            }
+        }
+        Forge::Gitlab => calculate(gitlab_client(&args)?, args),
+        Forge::Gitea => calculate(gitea_client(&args)?, args),
+        Forge::Bitbucket => calculate(bitbucket_client(&args, false)?, args),
+        Forge::BitbucketDataCenter => calculate(bitbucket_client(&args, true)?, args),
+        Forge::Local => calculate(local_git_client(&args)?, args),
     }
-    let client = client_builder.build().await?;
+}
+
+/// Rejects arguments not supported by forge, rather than silently ignoring them.
+fn check_forge_args(args: &Args) -> Result<()> {
+    if args.forge != Forge::Github && (args.pacing != Pacing::Burst || args.rate_limit_reserve > 0.0) {
+        return Err(Error::Error(format!(
+            "--pacing and --rate-limit-reserve are supported only by GitHub, not by {}",
+            args.forge.as_ref()
+        )));
+    }
+    if let Some(sort) = args.sort.as_ref().filter(|sort| !args.forge.sorts().contains(sort)) {
+        let supported = args.forge.sorts().iter().map(Sort::as_ref).collect::<Vec<_>>();
+        return Err(Error::Error(format!(
+            "--sort {} is not supported by {}, supported orders: [{}]",
+            sort.as_ref(),
+            args.forge.as_ref(),
+            supported.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn github_client_builder(args: &Args) -> Result<GithubClientBuilder> {
+    let mut client_builder = GithubClientBuilder::default()
+        .with_anonymous_contributors(args.anonymous)
+        .with_retry_policy(retry_policy(args))
+        .with_pacing(args.pacing)
+        .with_reserve(args.rate_limit_reserve)
+        .with_request_timeout(Duration::from_secs(args.request_timeout_secs))
+        .with_max_history_commits(args.max_history_commits);
+    if let Some(api_url) = &args.api_url {
+        client_builder = client_builder.with_github_url(api_url);
+    }
+    if let Some(token) = &args.api_token {
+        client_builder = client_builder.try_with_token(token.clone())?; //TODO ideally in builder the only `try_` method should be .build()
+    }
+    Ok(client_builder)
+}
+
+fn gitlab_client(args: &Args) -> Result<GitlabClient> {
+    let mut client_builder = GitlabClientBuilder::default()
+        .with_retry_policy(retry_policy(args))
+        .with_request_timeout(Duration::from_secs(args.request_timeout_secs));
+    if let Some(api_url) = &args.api_url {
+        client_builder = client_builder.with_gitlab_url(api_url);
+    }
+    client_builder = match &args.api_token {
+        Some(token) if args.oauth_token => client_builder.try_with_oauth_token(token.clone())?,
+        Some(token) => client_builder.try_with_token(token.clone())?,
+        None => client_builder,
+    };
+    client_builder.build()
+}
+
+fn gitea_client(args: &Args) -> Result<GiteaClient> {
+    let mut client_builder = GiteaClientBuilder::default()
+        .with_anonymous_contributors(args.anonymous)
+        .with_max_history_commits(args.max_history_commits)
+        .with_retry_policy(retry_policy(args))
+        .with_request_timeout(Duration::from_secs(args.request_timeout_secs));
+    if let Some(api_url) = &args.api_url {
+        client_builder = client_builder.with_gitea_url(api_url);
+    }
+    if let Some(token) = &args.api_token {
+        client_builder = client_builder.try_with_token(token.clone())?;
+    }
+    client_builder.build()
+}
+
+fn bitbucket_client(args: &Args, data_center: bool) -> Result<BitbucketClient> {
+    let mut client_builder = BitbucketClientBuilder::default()
+        .with_data_center(data_center)
+        .with_workspace(args.workspace.clone())
+        .with_anonymous_contributors(args.anonymous)
+        .with_max_history_commits(args.max_history_commits)
+        .with_retry_policy(retry_policy(args))
+        .with_request_timeout(Duration::from_secs(args.request_timeout_secs));
+    if let Some(api_url) = &args.api_url {
+        client_builder = client_builder.with_bitbucket_url(api_url);
+    }
+    client_builder = match (&args.api_token, &args.api_username) {
+        (Some(token), Some(username)) => client_builder.try_with_app_password(username, token.clone())?,
+        (Some(token), None) => client_builder.try_with_oauth_token(token.clone())?,
+        (None, _) => client_builder,
+    };
+    client_builder.build()
+}
 
-    let calculator = BusFactorCalculator::new(client, args.threshold);
-    Ok(calculator.calculate(
+fn retry_policy(args: &Args) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: args.max_attempts,
+        initial_backoff: Duration::from_millis(args.retry_backoff_ms),
+        max_backoff: Duration::from_millis(args.max_retry_backoff_ms),
+    }
+}
+
+fn local_git_client(args: &Args) -> Result<LocalGitClient> {
+    let repos_dir = args
+        .repos_dir
+        .as_ref()
+        .ok_or_else(|| Error::Error("Local forge requires --repos-dir".to_string()))?;
+    LocalGitClientBuilder::new(repos_dir)
+        .with_branch(args.branch.clone())
+        .with_since(args.since)
+        .with_until(args.until)
+        .with_max_history_commits(args.max_history_commits)
+        .build()
+}
+
+fn calculate<
+    REPO: Repo + 'static,
+    CLIENT: Client<REPO, MAX_REPOS_PAGE, MAX_CONTRIBUTORS_PAGE, FIRST_PAGE_NUMBER> + 'static,
+    const MAX_REPOS_PAGE: u32,
+    const MAX_CONTRIBUTORS_PAGE: u32,
+    const FIRST_PAGE_NUMBER: u32,
+>(
+    client: CLIENT,
+    args: Args,
+) -> Result<(BusFactorStream, RunStats)> {
+    let contributor_filter = contributor_filter(&args)?;
+    let contributor_aliases = contributor_aliases(&args)?;
+
+    let threshold = args.threshold;
+    let calculator = match args.metric {
+        Metric::TopShare => BusFactorCalculator::new(client, TopShare { threshold }),
+        Metric::Gini => BusFactorCalculator::new(client, Gini { threshold }),
+        Metric::Entropy => BusFactorCalculator::new(client, Entropy { threshold }),
+        Metric::Herfindahl => BusFactorCalculator::new(client, Herfindahl { threshold }),
+        Metric::TruckFactor => BusFactorCalculator::new(
+            client,
+            TruckFactor {
+                threshold,
+                max_factor: args.max_factor,
+            },
+        ),
+    };
+    let mut calculator = calculator
+        .with_contributors_window(args.contributors_window)
+        .with_contributor_filter(contributor_filter)
+        .with_contributor_aliases(contributor_aliases)
+        .with_early_exit(!args.no_early_exit)
+        .with_full_report(args.full_report)
+        .with_adaptive_concurrency(args.adaptive_concurrency)
+        .with_unordered(args.unordered);
+    if let Some(deadline_secs) = args.deadline_secs {
+        calculator = calculator.with_deadline(Duration::from_secs(deadline_secs));
+    }
+    let stats = calculator.stats();
+    // Bitbucket Data Center ignores order, as it lists repositories in name order
+    let sort = args
+        .sort
+        .or_else(|| args.forge.sorts().first().cloned())
+        .unwrap_or(Sort::Updated);
+    let results = calculator.calculate(
         args.language,
         args.project_count,
         args.max_repo_req as usize,
         args.max_contrib_req as usize,
-        args.sort,
-    ))
+        sort,
+    );
+    Ok((results, stats))
+}
+
+fn contributor_filter(args: &Args) -> Result<ContributorFilter> {
+    let filter = if args.include_bots {
+        ContributorFilter::none()
+    } else {
+        ContributorFilter::default()
+    };
+    let mut patterns = args.exclude.clone();
+    if let Some(exclude_file) = &args.exclude_file {
+        patterns.extend(read_patterns(exclude_file)?);
+    }
+    filter.try_with_patterns(patterns)
+}
+
+/// Aliases file with `.toml` extension is read as TOML alias table, any other as `.mailmap`.
+fn contributor_aliases(args: &Args) -> Result<ContributorAliases> {
+    match &args.aliases_file {
+        Some(path) if path.extension().is_some_and(|extension| extension == "toml") => {
+            ContributorAliases::from_toml(&read_file(path)?)
+        }
+        Some(path) => ContributorAliases::from_mailmap(&read_file(path)?),
+        None => Ok(ContributorAliases::default()),
+    }
+}
+
+fn read_patterns(path: &Path) -> Result<Vec<String>> {
+    let content = read_file(path)?;
+    let patterns = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    Ok(patterns)
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|err| Error::Error(format!("Failed to read {}: {}", path.display(), err)))
 }
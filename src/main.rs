@@ -1,27 +1,92 @@
-use bus_factor::{api::Error, BusFactor};
+use bus_factor::api::Error;
+use bus_factor::Failure;
 use bus_factor_app::args::Args;
+use bus_factor_app::output::{FailureSummary, Output};
 use clap::Parser;
 use dotenv::dotenv;
 use futures::StreamExt;
+use log::{error, warn};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+
+/// Exit code of run stopped by deadline or interrupt, after partial results have been reported.
+const STOPPED_EXIT_CODE: u8 = 2;
 
 #[tokio::main]
-async fn main() -> Result<(), Error> {
+async fn main() -> Result<ExitCode, Error> {
     dotenv().ok();
     let args = Args::parse();
+    let project_count = args.project_count;
+    let max_failure_ratio = args.max_failure_ratio;
 
-    bus_factor_app::calculate_bus_factor(args)
-        .await?
-        .for_each(print_line)
-        .await;
+    let writer: Box<dyn Write + Send> = match &args.output_file {
+        Some(path) => {
+            let file = File::create(path)
+                .map_err(|err| Error::Error(format!("Failed to create {}: {}", path.display(), err)))?;
+            Box::new(BufWriter::new(file))
+        }
+        None => Box::new(std::io::stdout()),
+    };
+    let mut output = Output::new(args.output_format, writer, args.verbose, args.full_report)
+        .with_summary_in_output(args.summary_in_output);
+    let mut failures = FailureSummary::default();
 
-    Ok(())
-}
+    // Listens for Ctrl-C from the start, so interrupt during setup is not lost
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let (mut results, stats) = tokio::select! {
+        calculation = bus_factor_app::calculate_bus_factor(args) => calculation?,
+        _ = &mut ctrl_c => {
+            warn!("Interrupted before analysis started.");
+            return Ok(ExitCode::from(STOPPED_EXIT_CODE));
+        }
+    };
+    let mut stopped = None;
+    output.begin()?;
+    loop {
+        let result = tokio::select! {
+            result = results.next() => result,
+            _ = &mut ctrl_c => {
+                warn!("Interrupted. Reporting partial results.");
+                stopped = Some("Interrupted".to_string());
+                break;
+            }
+        };
+        match result {
+            Some(Ok(bus_factor)) => output.write(&bus_factor)?,
+            Some(Err(failure @ Failure::DeadlineExceeded(_))) => {
+                warn!("{}. Reporting partial results.", failure);
+                stopped = Some(failure.to_string());
+            }
+            Some(Err(failure)) => {
+                error!("{}", failure);
+                failures.add(&failure);
+            }
+            None => break,
+        }
+    }
+    // Aborts requests in progress
+    drop(results);
+    let summary = stats.summary();
+    output.finish(&summary)?;
 
-//TODO only because of for_each
-async fn print_line(bus_factor: BusFactor) {
-    let line = format!(
-        "project: {0: <15} user: {1: <20} percentage: {2}",
-        bus_factor.repo, bus_factor.contributor, bus_factor.percentage
-    );
-    println!("{}", line);
+    if !output.writes_summary() {
+        eprintln!("{}", summary);
+    }
+    if failures.failed_repos() > 0 {
+        eprintln!("{}", failures);
+    }
+    let failure_ratio = failures.ratio(project_count);
+    if failure_ratio > max_failure_ratio {
+        return Err(Error::Error(format!(
+            "Failure ratio {:.2} exceeds maximal failure ratio {:.2}",
+            failure_ratio, max_failure_ratio
+        )));
+    }
+    if let Some(reason) = stopped {
+        eprintln!("{}. Results are partial.", reason);
+        return Ok(ExitCode::from(STOPPED_EXIT_CODE));
+    }
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,267 @@
+use bus_factor::api::{Error, Result};
+use bus_factor::{BusFactor, Failure, RunSummary};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Write;
+use strum_macros::{AsRefStr, EnumString};
+
+#[derive(Debug, EnumString, Clone, Copy, AsRefStr, PartialEq)]
+pub enum OutputFormat {
+    #[strum(serialize = "text")]
+    Text,
+    /// JSON array
+    #[strum(serialize = "json")]
+    Json,
+    /// JSON object per line, written as soon as result arrives
+    #[strum(serialize = "ndjson")]
+    Ndjson,
+    #[strum(serialize = "csv")]
+    Csv,
+    /// Markdown table
+    #[strum(serialize = "markdown")]
+    Markdown,
+}
+
+const COLUMNS: &[&str] = &[
+    "repo",
+    "rank",
+    "full_name",
+    "owner",
+    "html_url",
+    "stars",
+    "forks",
+    "open_issues",
+    "default_branch",
+    "pushed_at",
+    "archived",
+    "fork",
+    "factor",
+    "contributors",
+    "score",
+    "status",
+    "total_contributions",
+    "contributor_count",
+    "excluded",
+    "truncated",
+];
+
+/// Writes `BusFactor` results in given format.
+pub struct Output {
+    format: OutputFormat,
+    writer: Box<dyn Write + Send>,
+    verbose: bool,
+    full_report: bool,
+    summary_in_output: bool,
+    count: usize,
+}
+
+impl Output {
+    /// # Arguments
+    /// * `verbose` - Text format reports repository stats, excluded contributors and truncation of contributors
+    /// * `full_report` - Text format reports status of repository, whether it is at risk
+    pub fn new(format: OutputFormat, writer: Box<dyn Write + Send>, verbose: bool, full_report: bool) -> Self {
+        Output {
+            format,
+            writer,
+            verbose,
+            full_report,
+            summary_in_output: false,
+            count: 0,
+        }
+    }
+
+    /// Include `RunSummary` in JSON output (as `summary` next to `results` array),
+    /// or NDJSON output (as last line with `summary`). Disabled by default.
+    pub fn with_summary_in_output(mut self, summary_in_output: bool) -> Self {
+        self.summary_in_output = summary_in_output;
+        self
+    }
+
+    /// Whether `RunSummary` is written by `finish`.
+    pub fn writes_summary(&self) -> bool {
+        self.summary_in_output && matches!(self.format, OutputFormat::Json | OutputFormat::Ndjson)
+    }
+
+    /// Writes header, or opening of JSON array.
+    pub fn begin(&mut self) -> Result<()> {
+        match self.format {
+            OutputFormat::Json if self.writes_summary() => write!(self.writer, "{{\"results\": ["),
+            OutputFormat::Json => write!(self.writer, "["),
+            OutputFormat::Csv => writeln!(self.writer, "{}", COLUMNS.join(",")),
+            OutputFormat::Markdown => {
+                let separator = vec!["---"; COLUMNS.len()];
+                writeln!(self.writer, "| {} |", COLUMNS.join(" | "))
+                    .and_then(|_| writeln!(self.writer, "| {} |", separator.join(" | ")))
+            }
+            OutputFormat::Text | OutputFormat::Ndjson => Ok(()),
+        }
+        .map_err(write_error)
+    }
+
+    pub fn write(&mut self, bus_factor: &BusFactor) -> Result<()> {
+        match self.format {
+            OutputFormat::Text => writeln!(self.writer, "{}", self.text_line(bus_factor)).map_err(write_error),
+            OutputFormat::Json => {
+                let separator = if self.count == 0 { "\n" } else { ",\n" };
+                write!(self.writer, "{}{}", separator, to_json(bus_factor)?).map_err(write_error)
+            }
+            OutputFormat::Ndjson => writeln!(self.writer, "{}", to_json(bus_factor)?).map_err(write_error),
+            OutputFormat::Csv => {
+                let row = fields(bus_factor)
+                    .iter()
+                    .map(|field| csv_escape(field))
+                    .collect::<Vec<_>>();
+                writeln!(self.writer, "{}", row.join(",")).map_err(write_error)
+            }
+            OutputFormat::Markdown => {
+                let row = fields(bus_factor)
+                    .iter()
+                    .map(|field| markdown_escape(field))
+                    .collect::<Vec<_>>();
+                writeln!(self.writer, "| {} |", row.join(" | ")).map_err(write_error)
+            }
+        }?;
+        self.count += 1;
+        self.writer.flush().map_err(write_error)
+    }
+
+    /// Writes closing of JSON array, `summary` if `writes_summary`, and flushes output.
+    pub fn finish(&mut self, summary: &RunSummary) -> Result<()> {
+        match self.format {
+            OutputFormat::Json if self.writes_summary() => {
+                writeln!(self.writer, "\n], \"summary\": {}}}", to_json(summary)?)
+            }
+            OutputFormat::Json => writeln!(self.writer, "\n]"),
+            OutputFormat::Ndjson if self.writes_summary() => {
+                writeln!(self.writer, "{{\"summary\": {}}}", to_json(summary)?)
+            }
+            _ => Ok(()),
+        }
+        .map_err(write_error)?;
+        self.writer.flush().map_err(write_error)
+    }
+
+    fn text_line(&self, bus_factor: &BusFactor) -> String {
+        let mut line = format!(
+            "project: {0: <30} bus factor: {1: <3} users: {2: <20} score: {3}",
+            bus_factor.details.full_name,
+            bus_factor.factor,
+            bus_factor.contributors.join(", "),
+            bus_factor.score
+        );
+        if self.full_report {
+            line.push_str(&format!(" status: {}", status(bus_factor)));
+        }
+        if self.verbose {
+            line.push_str(&format!(
+                " stars: {} contributors: {} contributions: {}",
+                bus_factor.details.stars, bus_factor.contributor_count, bus_factor.total_contributions
+            ));
+        }
+        if self.verbose && !bus_factor.excluded.is_empty() {
+            line.push_str(&format!(" excluded: {}", bus_factor.excluded.join(", ")));
+        }
+        if self.verbose && bus_factor.truncated {
+            line.push_str(" (contributors truncated)");
+        }
+        line
+    }
+}
+
+/// Number of failures by kind.
+#[derive(Default)]
+pub struct FailureSummary {
+    counts: BTreeMap<String, u32>,
+    failed_repos: u32,
+}
+
+impl FailureSummary {
+    pub fn add(&mut self, failure: &Failure) {
+        let kind = match failure.error() {
+            Some(error) => format!("{} ({})", failure.as_ref(), error.as_ref()),
+            None => failure.as_ref().to_string(),
+        };
+        *self.counts.entry(kind).or_default() += 1;
+        self.failed_repos += failure.failed_repos();
+    }
+
+    /// Number of repositories not analyzed because of failures.
+    pub fn failed_repos(&self) -> u32 {
+        self.failed_repos
+    }
+
+    /// Ratio of failed repositories to `repo_count` searched repositories.
+    pub fn ratio(&self, repo_count: u32) -> f32 {
+        match repo_count {
+            0 => 0.0,
+            repo_count => self.failed_repos as f32 / repo_count as f32,
+        }
+    }
+}
+
+impl Display for FailureSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = self
+            .counts
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .collect::<Vec<_>>();
+        write!(f, "Failed repositories: {} ({})", self.failed_repos, counts.join(", "))
+    }
+}
+
+/// Values of `COLUMNS`.
+fn fields(bus_factor: &BusFactor) -> Vec<String> {
+    let details = &bus_factor.details;
+    vec![
+        bus_factor.repo.clone(),
+        bus_factor.rank.to_string(),
+        details.full_name.clone(),
+        details.owner.clone(),
+        details.html_url.clone(),
+        details.stars.to_string(),
+        details.forks.to_string(),
+        details.open_issues.to_string(),
+        details.default_branch.clone(),
+        details.pushed_at.clone().unwrap_or_default(),
+        details.archived.to_string(),
+        details.fork.to_string(),
+        bus_factor.factor.to_string(),
+        bus_factor.contributors.join(" "),
+        bus_factor.score.to_string(),
+        status(bus_factor).to_string(),
+        bus_factor.total_contributions.to_string(),
+        bus_factor.contributor_count.to_string(),
+        bus_factor.excluded.join(" "),
+        bus_factor.truncated.to_string(),
+    ]
+}
+
+/// Status of repository: `at-risk` if it has reached bus factor condition, `ok` otherwise.
+fn status(bus_factor: &BusFactor) -> &'static str {
+    if bus_factor.reached {
+        "at-risk"
+    } else {
+        "ok"
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<String> {
+    serde_json::to_string(value).map_err(|err| Error::Error(format!("Failed to serialize result: {}", err)))
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown_escape(field: &str) -> String {
+    field.replace('|', "\\|").replace('\n', " ")
+}
+
+fn write_error(err: std::io::Error) -> Error {
+    Error::Error(format!("Failed to write output: {}", err))
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use bus_factor::api::RepoDetails;
+use bus_factor::BusFactor;
+use bus_factor_app::args::Args;
+use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
+use futures::StreamExt;
+use secrecy::SecretString;
+use serde_json::json;
+use std::collections::VecDeque;
+use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MAX_PAGE: u32 = 100;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn bitbucket_happy_path_200() {
+    let server = MockServer::start().await;
+
+    const REPOS_COUNT: u32 = 200;
+    // Every Nth repo will have a large bus factor, with history longer than one page
+    const BUS_FACTOR_DIVISOR: u32 = 5;
+    const LANG: &str = "Rust";
+
+    mock_repos(&server, REPOS_COUNT).await;
+    let mut expected_bus_factors = mock_commits(&server, REPOS_COUNT, BUS_FACTOR_DIVISOR).await;
+
+    // Repositories are sorted by last update, the only order of Bitbucket Cloud
+    let args = Args {
+        language: LANG.to_string(),
+        project_count: REPOS_COUNT,
+        api_token: Some(SecretString::new("secret".to_string())),
+        api_username: Some("user".to_string()),
+        ..common::args(Forge::Bitbucket, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let calculated_bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+
+    let summary = stats.summary();
+    assert_eq!(summary.repos_analyzed, REPOS_COUNT);
+    let bus_factor_repos = REPOS_COUNT / BUS_FACTOR_DIVISOR;
+    assert_eq!(
+        summary.resources["bitbucket"].requests,
+        (REPOS_COUNT / MAX_PAGE + REPOS_COUNT + bus_factor_repos) as u64,
+        "Repositories with a bus factor should have two pages of commits"
+    );
+
+    assert_eq!(expected_bus_factors.len(), calculated_bus_factors.len());
+    for bus_factor in calculated_bus_factors {
+        match expected_bus_factors.pop_front() {
+            Some(expected_factor) => assert_eq!(bus_factor, expected_factor),
+            None => panic!("Got unexpected result: {:?}", bus_factor),
+        }
+    }
+}
+
+/// Pages of repositories are linked with `next`, following pages have `page` parameter.
+async fn mock_repos(server: &MockServer, repos_count: u32) {
+    let pages = repos_count / MAX_PAGE;
+    for repo_page in 0..pages {
+        let repos = (0..MAX_PAGE)
+            .map(|repo_page_index| {
+                let repo_index = repo_page * MAX_PAGE + repo_page_index;
+                json!({
+                    "slug": format!("repo_{}", repo_index),
+                    "full_name": format!("workspace_{}/repo_{}", repo_index, repo_index),
+                    "workspace": { "slug": format!("workspace_{}", repo_index) },
+                    "links": { "html": { "href": format!("https://bitbucket.org/workspace_{}/repo_{}", repo_index, repo_index) } },
+                    "mainbranch": { "name": "main" }
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut body = json!({ "pagelen": MAX_PAGE, "values": repos });
+        if repo_page + 1 < pages {
+            body["next"] = json!(format!(
+                "{}/repositories?pagelen={}&page={}",
+                server.uri(),
+                MAX_PAGE,
+                repo_page + 2
+            ));
+        }
+        let mock = Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .and(query_param("pagelen", format!("{}", MAX_PAGE)))
+            .and(header("Authorization", "Basic dXNlcjpzZWNyZXQ="));
+        let mock = match repo_page {
+            0 => mock
+                .and(query_param("q", "language=\"rust\""))
+                .and(query_param("sort", "-updated_on"))
+                .and(query_param_is_missing("page")),
+            page => mock.and(query_param("page", format!("{}", page + 1))),
+        };
+        mock.respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+}
+
+/// Repositories with a bus factor have full page of commits of the top author, and second page of 10 others.
+/// Other repositories have 20 commits of 20 authors.
+async fn mock_commits(server: &MockServer, repos_count: u32, bus_factor_divisor: u32) -> VecDeque<BusFactor> {
+    let mut bus_factors = VecDeque::new();
+    for repo_index in 0..repos_count {
+        let commits_path = format!("/repositories/workspace_{}/repo_{}/commits", repo_index, repo_index);
+        if repo_index % bus_factor_divisor == 0 {
+            let top_author = format!("author_{}_top", repo_index);
+            let first_page = (0..MAX_PAGE).map(|_| commit(&top_author)).collect::<Vec<_>>();
+            let second_page = (0..10)
+                .map(|index| commit(&format!("author_{}_{}", repo_index, index)))
+                .collect::<Vec<_>>();
+            let next = format!(
+                "{}{}?include=main&pagelen={}&page=2",
+                server.uri(),
+                commits_path,
+                MAX_PAGE
+            );
+            mock_commits_page(server, &commits_path, None, first_page, Some(next)).await;
+            mock_commits_page(server, &commits_path, Some(2), second_page, None).await;
+            let details = RepoDetails {
+                owner: format!("workspace_{}", repo_index),
+                full_name: format!("workspace_{}/repo_{}", repo_index, repo_index),
+                html_url: format!("https://bitbucket.org/workspace_{}/repo_{}", repo_index, repo_index),
+                default_branch: "main".to_string(),
+                ..RepoDetails::default()
+            };
+            bus_factors.push_back(BusFactor {
+                total_contributions: MAX_PAGE + 10,
+                contributor_count: 11,
+                ..BusFactor::new(format!("repo_{}", repo_index), 1, vec![top_author], 0.91)
+                    .with_rank(repo_index + 1)
+                    .with_details(details)
+            });
+        } else {
+            let page = (0..20)
+                .map(|index| commit(&format!("author_{}_{}", repo_index, index)))
+                .collect::<Vec<_>>();
+            mock_commits_page(server, &commits_path, None, page, None).await;
+        }
+    }
+    bus_factors
+}
+
+async fn mock_commits_page(
+    server: &MockServer,
+    commits_path: &str,
+    page: Option<u32>,
+    commits: Vec<serde_json::Value>,
+    next: Option<String>,
+) {
+    let mock = Mock::given(method("GET"))
+        .and(path(commits_path))
+        .and(query_param("include", "main"));
+    let mock = match page {
+        Some(page) => mock.and(query_param("page", format!("{}", page))),
+        None => mock.and(query_param_is_missing("page")),
+    };
+    mock.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "values": commits, "next": next })))
+        .mount(server)
+        .await;
+}
+
+fn commit(nickname: &str) -> serde_json::Value {
+    json!({
+        "hash": "0000",
+        "author": {
+            "raw": format!("{} <{}@example.com>", nickname, nickname),
+            "user": { "nickname": nickname }
+        }
+    })
+}
@@ -0,0 +1,54 @@
+use bus_factor::calculator::ContributorsWindow;
+use bus_factor::metric::Metric;
+use bus_factor_app::args::Args;
+use bus_factor_app::forge::Forge;
+use bus_factor_app::output::OutputFormat;
+use github_client::{GithubApi, Pacing};
+
+/// Arguments with command line defaults, analyzing top 4 `rust` repositories of given forge.
+pub fn args(forge: Forge, api_url: Option<String>) -> Args {
+    Args {
+        language: "rust".to_string(),
+        project_count: 4,
+        forge,
+        api_token: None,
+        oauth_token: false,
+        api_username: None,
+        workspace: None,
+        repos_dir: None,
+        branch: None,
+        since: None,
+        until: None,
+        api_url,
+        github_api: GithubApi::Rest,
+        max_history_commits: 1000,
+        metric: Metric::TruckFactor,
+        threshold: 0.75,
+        max_factor: 1,
+        contributors_window: ContributorsWindow::Top(25),
+        no_early_exit: false,
+        max_failure_ratio: 0.1,
+        max_attempts: 3,
+        retry_backoff_ms: 500,
+        max_retry_backoff_ms: 30000,
+        pacing: Pacing::Burst,
+        rate_limit_reserve: 0.0,
+        adaptive_concurrency: false,
+        unordered: false,
+        summary_in_output: false,
+        request_timeout_secs: 30,
+        deadline_secs: None,
+        full_report: false,
+        exclude: Vec::new(),
+        exclude_file: None,
+        include_bots: false,
+        aliases_file: None,
+        anonymous: false,
+        verbose: false,
+        output_format: OutputFormat::Text,
+        output_file: None,
+        max_repo_req: 1,
+        max_contrib_req: 10,
+        sort: None,
+    }
+}
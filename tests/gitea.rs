@@ -0,0 +1,197 @@
+mod common;
+
+use bus_factor::api::RepoDetails;
+use bus_factor::BusFactor;
+use bus_factor_app::args::Args;
+use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
+use futures::StreamExt;
+use secrecy::SecretString;
+use serde_json::json;
+use std::collections::VecDeque;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MAX_PAGE: u32 = 50;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn gitea_happy_path_100() {
+    let server = MockServer::start().await;
+
+    const REPOS_COUNT: u32 = 100;
+    // Every Nth repo will have a large bus factor, with history longer than one page
+    const BUS_FACTOR_DIVISOR: u32 = 5;
+    const LANG: &str = "Rust";
+
+    mock_repos(&server, REPOS_COUNT).await;
+    let mut expected_bus_factors = mock_commits(&server, REPOS_COUNT, BUS_FACTOR_DIVISOR).await;
+
+    let args = Args {
+        language: LANG.to_string(),
+        project_count: REPOS_COUNT,
+        api_token: Some(SecretString::new("secret".to_string())),
+        ..common::args(Forge::Gitea, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let calculated_bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+
+    let summary = stats.summary();
+    assert_eq!(summary.repos_analyzed, REPOS_COUNT);
+    let bus_factor_repos = REPOS_COUNT / BUS_FACTOR_DIVISOR;
+    assert_eq!(
+        summary.resources["gitea"].requests,
+        (REPOS_COUNT / MAX_PAGE + REPOS_COUNT + bus_factor_repos) as u64,
+        "Repositories with a bus factor should have two pages of commits"
+    );
+
+    assert_eq!(expected_bus_factors.len(), calculated_bus_factors.len());
+    for bus_factor in calculated_bus_factors {
+        match expected_bus_factors.pop_front() {
+            Some(expected_factor) => assert_eq!(bus_factor, expected_factor),
+            None => panic!("Got unexpected result: {:?}", bus_factor),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn gitea_small_pages_truncated() {
+    let server = MockServer::start().await;
+
+    // Instance limits pages to 30 commits, fewer than requested
+    const PAGE: u32 = 30;
+    let repo = json!({
+        "name": "repo",
+        "full_name": "owner/repo",
+        "owner": { "login": "owner" },
+        "default_branch": "main",
+        "empty": false
+    });
+    Mock::given(method("GET"))
+        .and(path("/repos/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true, "data": [repo] })))
+        .mount(&server)
+        .await;
+    for (page, author) in [(1, "alice"), (2, "bob"), (3, "carol")] {
+        let commits = (0..PAGE).map(|_| commit(author)).collect::<Vec<_>>();
+        mock_commits_page(&server, "/repos/owner/repo/commits", page, commits, 3 * PAGE).await;
+    }
+
+    let args = Args {
+        project_count: 1,
+        max_history_commits: 45,
+        // Bus factor of counted commits is not reached
+        full_report: true,
+        ..common::args(Forge::Gitea, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+
+    let counted = bus_factors
+        .iter()
+        .map(|bus_factor| (bus_factor.total_contributions, bus_factor.contributor_count))
+        .collect::<Vec<_>>();
+    assert_eq!(counted, vec![(45, 2)], "Commits should be counted up to the limit");
+    assert_eq!(
+        stats.summary().resources["gitea"].requests,
+        3,
+        "Short pages should not end history"
+    );
+}
+
+async fn mock_repos(server: &MockServer, repos_count: u32) {
+    for repo_page in 0..repos_count / MAX_PAGE {
+        let repos = (0..MAX_PAGE)
+            .map(|repo_page_index| {
+                let repo_index = repo_page * MAX_PAGE + repo_page_index;
+                json!({
+                    "name": format!("repo_{}", repo_index),
+                    "full_name": format!("owner_{}/repo_{}", repo_index, repo_index),
+                    "owner": { "login": format!("owner_{}", repo_index) },
+                    "stars_count": repos_count - repo_index,
+                    "default_branch": "main",
+                    "empty": false
+                })
+            })
+            .collect::<Vec<_>>();
+        Mock::given(method("GET"))
+            .and(path("/repos/search"))
+            .and(query_param("q", "rust"))
+            .and(query_param("topic", "true"))
+            .and(query_param("sort", "stars"))
+            .and(query_param("limit", format!("{}", MAX_PAGE)))
+            .and(query_param("page", format!("{}", repo_page + 1)))
+            .and(header("Authorization", "token secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true, "data": repos })))
+            .mount(server)
+            .await;
+    }
+}
+
+/// Repositories with a bus factor have full page of commits of the top author, and second page of 10 others.
+/// Other repositories have 20 commits of 20 authors.
+async fn mock_commits(server: &MockServer, repos_count: u32, bus_factor_divisor: u32) -> VecDeque<BusFactor> {
+    let mut bus_factors = VecDeque::new();
+    for repo_index in 0..repos_count {
+        let commits_path = format!("/repos/owner_{}/repo_{}/commits", repo_index, repo_index);
+        if repo_index % bus_factor_divisor == 0 {
+            let top_author = format!("author_{}_top", repo_index);
+            let first_page = (0..MAX_PAGE).map(|_| commit(&top_author)).collect::<Vec<_>>();
+            let second_page = (0..10)
+                .map(|index| commit(&format!("author_{}_{}", repo_index, index)))
+                .collect::<Vec<_>>();
+            mock_commits_page(server, &commits_path, 1, first_page, MAX_PAGE + 10).await;
+            mock_commits_page(server, &commits_path, 2, second_page, MAX_PAGE + 10).await;
+            let details = RepoDetails {
+                owner: format!("owner_{}", repo_index),
+                full_name: format!("owner_{}/repo_{}", repo_index, repo_index),
+                stars: repos_count - repo_index,
+                default_branch: "main".to_string(),
+                ..RepoDetails::default()
+            };
+            bus_factors.push_back(BusFactor {
+                total_contributions: MAX_PAGE + 10,
+                contributor_count: 11,
+                ..BusFactor::new(format!("repo_{}", repo_index), 1, vec![top_author], 0.83)
+                    .with_rank(repo_index + 1)
+                    .with_details(details)
+            });
+        } else {
+            let page = (0..20)
+                .map(|index| commit(&format!("author_{}_{}", repo_index, index)))
+                .collect::<Vec<_>>();
+            mock_commits_page(server, &commits_path, 1, page, 20).await;
+        }
+    }
+    bus_factors
+}
+
+/// Page of commits, with total number of commits in `X-Total-Count` header.
+async fn mock_commits_page(
+    server: &MockServer,
+    commits_path: &str,
+    page: u32,
+    commits: Vec<serde_json::Value>,
+    total: u32,
+) {
+    Mock::given(method("GET"))
+        .and(path(commits_path))
+        .and(query_param("sha", "main"))
+        .and(query_param("page", format!("{}", page)))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-total-count", total.to_string().as_str())
+                .set_body_json(commits),
+        )
+        .mount(server)
+        .await;
+}
+
+fn commit(login: &str) -> serde_json::Value {
+    json!({
+        "sha": "0000",
+        "commit": { "author": { "name": login, "email": format!("{}@example.com", login) } },
+        "author": { "login": login }
+    })
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use bus_factor::calculator::Failure;
+use bus_factor::BusFactor;
+use bus_factor_app::args::Args;
+use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn github_primary_rate_limit() {
+    let server = MockServer::start().await;
+    let reset = Utc::now().timestamp() + 1;
+
+    mock_rate_limit(&server, reset).await;
+    mock_search(&server, reset, &["repo_0"]).await;
+    // Exhausted primary rate limit is answered without `Retry-After` header
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo_0/contributors"))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .set_body_json(json!({ "message": "API rate limit exceeded for 127.0.0.1." }))
+                .insert_header("x-ratelimit-limit", "5000")
+                .insert_header("x-ratelimit-remaining", "0")
+                .insert_header("x-ratelimit-reset", reset.to_string().as_str()),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_contributors(
+        &server,
+        "repo_0",
+        reset,
+        json!([contributor("alice", 10), contributor("bob", 1)]),
+    )
+    .await;
+
+    let args = Args {
+        project_count: 1,
+        ..common::args(Forge::Github, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+    let reported = bus_factors
+        .iter()
+        .map(|bus_factor| (bus_factor.repo.as_str(), bus_factor.contributors.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(reported, vec![("repo_0", vec!["alice".to_string()])]);
+    assert_eq!(
+        stats.summary().resources["core"].requests,
+        2,
+        "Rate limited request should be re-issued after reset"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn github_contributors_responses() {
+    let server = MockServer::start().await;
+    let reset = Utc::now().timestamp() + 60;
+
+    mock_rate_limit(&server, reset).await;
+    mock_search(&server, reset, &["computed", "empty", "too_large"]).await;
+    // Contributors are being computed
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/computed/contributors"))
+        .respond_with(with_rate_limit(202, reset, "5000"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_contributors(
+        &server,
+        "computed",
+        reset,
+        json!([contributor("alice", 10), contributor("bob", 1)]),
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/empty/contributors"))
+        .respond_with(with_rate_limit(204, reset, "5000"))
+        .mount(&server)
+        .await;
+    let message = "The history or contributor list is too large to list contributors for this repository via the API.";
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/too_large/contributors"))
+        .respond_with(with_rate_limit(403, reset, "5000").set_body_json(json!({ "message": message })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/too_large/stats/contributors"))
+        .respond_with(with_rate_limit(202, reset, "5000"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    let stats = json!([
+        { "author": { "login": "dave", "type": "User" }, "total": 1 },
+        { "author": { "login": "carol", "type": "User" }, "total": 10 }
+    ]);
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/too_large/stats/contributors"))
+        .respond_with(with_rate_limit(200, reset, "5000").set_body_json(stats))
+        .mount(&server)
+        .await;
+
+    let args = Args {
+        project_count: 3,
+        ..common::args(Forge::Github, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let results: Vec<Result<BusFactor, Failure>> = results.collect().await;
+    let reported = results
+        .iter()
+        .map(|result| match result {
+            Ok(bus_factor) => Ok((bus_factor.repo.as_str(), bus_factor.contributors.clone())),
+            Err(Failure::RepoEmpty { repo }) => Err(repo.as_str()),
+            Err(failure) => panic!("Unexpected failure: {:?}", failure),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reported,
+        vec![
+            Ok(("computed", vec!["alice".to_string()])),
+            Err("empty"),
+            Ok(("too_large", vec!["carol".to_string()])),
+        ]
+    );
+    assert_eq!(
+        stats.summary().resources["core"].requests,
+        6,
+        "Contributors and statistics being computed should be polled"
+    );
+}
+
+async fn mock_rate_limit(server: &MockServer, reset: i64) {
+    let body = json!({
+        "resources": {
+            "core": { "limit": 5000, "remaining": 5000, "reset": reset },
+            "search": { "limit": 30, "remaining": 30, "reset": reset }
+        }
+    });
+    Mock::given(method("GET"))
+        .and(path("/rate_limit"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+async fn mock_search(server: &MockServer, reset: i64, repos: &[&str]) {
+    let items = repos
+        .iter()
+        .map(|repo| json!({ "name": repo, "full_name": format!("owner/{}", repo), "owner": { "login": "owner" } }))
+        .collect::<Vec<_>>();
+    let body = json!({
+        "total_count": repos.len(),
+        "incomplete_results": false,
+        "items": items
+    });
+    Mock::given(method("GET"))
+        .and(path("/search/repositories"))
+        .respond_with(with_rate_limit(200, reset, "30").set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+async fn mock_contributors(server: &MockServer, repo: &str, reset: i64, body: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path(format!("/repos/owner/{}/contributors", repo)))
+        .respond_with(with_rate_limit(200, reset, "5000").set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+fn with_rate_limit(status: u16, reset: i64, remaining: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status)
+        .insert_header("x-ratelimit-limit", remaining)
+        .insert_header("x-ratelimit-remaining", remaining)
+        .insert_header("x-ratelimit-reset", reset.to_string().as_str())
+}
+
+fn contributor(login: &str, contributions: u32) -> serde_json::Value {
+    json!({ "login": login, "contributions": contributions })
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use bus_factor::api::{RepoDetails, Sort};
+use bus_factor::BusFactor;
+use bus_factor_app::args::Args;
+use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
+use chrono::Utc;
+use futures::StreamExt;
+use github_client::Pacing;
+use rand::Rng;
+use secrecy::SecretString;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::time::Duration;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MAX_REPOS_PAGE: u32 = 100;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn gitlab_happy_path_200() {
+    let server = MockServer::start().await;
+
+    const REPOS_COUNT: u32 = 200;
+    // Every Nth repo will have a large bus factor
+    const BUS_FACTOR_DIVISOR: u32 = 4;
+    const REPO_CONTRBRS_COUNT: u32 = 25;
+    const LANG: &str = "rust";
+
+    mock_projects(&server, REPOS_COUNT, LANG).await;
+
+    let mut expected_bus_factors =
+        mock_contributors(&server, REPOS_COUNT, REPO_CONTRBRS_COUNT, BUS_FACTOR_DIVISOR).await;
+
+    let args = Args {
+        language: LANG.to_string(),
+        project_count: REPOS_COUNT,
+        api_token: Some(SecretString::new("token".to_string())),
+        ..common::args(Forge::Gitlab, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let calculated_bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+
+    let summary = stats.summary();
+    assert_eq!(summary.repos_analyzed, REPOS_COUNT);
+    assert_eq!(
+        summary.resources["gitlab"].requests,
+        (REPOS_COUNT / MAX_REPOS_PAGE + REPOS_COUNT) as u64
+    );
+
+    assert_eq!(
+        expected_bus_factors.len(),
+        calculated_bus_factors.len(),
+        "Every BUS_FACTOR_DIVISOR-th repo should have a bus factor"
+    );
+    for bus_factor in calculated_bus_factors {
+        match expected_bus_factors.pop_front() {
+            Some(expected_factor) => assert_eq!(bus_factor, expected_factor),
+            None => panic!("Got unexpected result: {:?}", bus_factor),
+        }
+    }
+}
+
+#[tokio::test]
+async fn gitlab_pacing_rejected() {
+    let args = Args {
+        pacing: Pacing::Smooth,
+        ..common::args(Forge::Gitlab, None)
+    };
+    let error = calculate_bus_factor(args)
+        .await
+        .err()
+        .expect("Pacing should be rejected");
+    assert!(error.to_string().contains("--pacing"), "{}", error);
+}
+
+#[tokio::test]
+async fn gitlab_sort_rejected() {
+    let args = Args {
+        sort: Some(Sort::Forks),
+        ..common::args(Forge::Gitlab, None)
+    };
+    let error = calculate_bus_factor(args)
+        .await
+        .err()
+        .expect("Sort by forks should be rejected");
+    assert!(error.to_string().contains("--sort forks"), "{}", error);
+}
+
+async fn mock_projects(server: &MockServer, repos_count: u32, lang: &str) {
+    for repo_page in 0..repos_count / MAX_REPOS_PAGE {
+        let projects = (0..MAX_REPOS_PAGE)
+            .map(|repo_page_index| {
+                let repo_index = repo_page * MAX_REPOS_PAGE + repo_page_index;
+                json!({
+                    "id": repo_index,
+                    "path": format!("repo_{}", repo_index),
+                    "path_with_namespace": format!("group_{}/repo_{}", repo_index, repo_index),
+                    "namespace": { "full_path": format!("group_{}", repo_index) },
+                    "star_count": repos_count - repo_index,
+                    "default_branch": "main"
+                })
+            })
+            .collect::<Vec<_>>();
+        let reset = format!("{}", Utc::now().timestamp() + 60);
+        Mock::given(method("GET"))
+            .and(path("/projects"))
+            .and(query_param("with_programming_language", lang))
+            .and(query_param("order_by", "star_count"))
+            .and(query_param("sort", "desc"))
+            .and(query_param("per_page", format!("{}", MAX_REPOS_PAGE)))
+            .and(query_param("page", format!("{}", repo_page + 1)))
+            .and(header("PRIVATE-TOKEN", "token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(projects)
+                    .set_delay(random_delay())
+                    .insert_header("RateLimit-Limit", "2000")
+                    .insert_header("RateLimit-Remaining", "1990")
+                    .insert_header("RateLimit-Reset", reset.as_str()),
+            )
+            .mount(server)
+            .await;
+    }
+}
+
+async fn mock_contributors(
+    server: &MockServer,
+    repos_count: u32,
+    repo_contributors_count: u32,
+    bus_factor_divisor: u32,
+) -> VecDeque<BusFactor> {
+    let mut bus_factors = VecDeque::new();
+    for repo_index in 0..repos_count {
+        let email = contributor_email(repo_index, 0);
+        let top_commits = match repo_index % bus_factor_divisor == 0 {
+            true => 1000,
+            false => 25,
+        };
+        let mut contributors = vec![contributor(&email, top_commits)];
+        contributors.extend(
+            (1..repo_contributors_count).map(|index| contributor(&contributor_email(repo_index, index), 25 - index)),
+        );
+        if top_commits == 1000 {
+            let details = RepoDetails {
+                owner: format!("group_{}", repo_index),
+                full_name: format!("group_{}/repo_{}", repo_index, repo_index),
+                stars: repos_count - repo_index,
+                default_branch: "main".to_string(),
+                ..RepoDetails::default()
+            };
+            bus_factors.push_back(BusFactor {
+                // Other contributors have 24 .. 1 commits
+                total_contributions: 1000 + (1..repo_contributors_count).map(|i| 25 - i).sum::<u32>(),
+                contributor_count: repo_contributors_count,
+                ..BusFactor::new(format!("repo_{}", repo_index), 1, vec![email], 0.77)
+                    .with_rank(repo_index + 1)
+                    .with_details(details)
+            });
+        }
+        Mock::given(method("GET"))
+            .and(path(format!("/projects/{}/repository/contributors", repo_index)))
+            .and(query_param("order_by", "commits"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(contributors)
+                    .set_delay(random_delay()),
+            )
+            .mount(server)
+            .await;
+    }
+    bus_factors
+}
+
+fn contributor(email: &str, commits: u32) -> serde_json::Value {
+    json!({ "name": "Contributor", "email": email, "commits": commits, "additions": 0, "deletions": 0 })
+}
+
+fn contributor_email(repo_index: u32, repo_contributor_index: u32) -> String {
+    format!("contributor_{}_{}@example.com", repo_index, repo_contributor_index)
+}
+
+fn random_delay() -> Duration {
+    Duration::from_millis(rand::thread_rng().gen_range(3..10))
+}
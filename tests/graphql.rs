@@ -0,0 +1,198 @@
+mod common;
+
+use bus_factor::calculator::Failure;
+use bus_factor::BusFactor;
+use bus_factor_app::args::Args;
+use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
+use chrono::Utc;
+use futures::StreamExt;
+use github_client::GithubApi;
+use serde_json::{json, Value};
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const LANG: &str = "rust";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn graphql_happy_path() {
+    let server = MockServer::start().await;
+
+    mock_rate_limit(&server).await;
+    mock_search(&server).await;
+    mock_history(&server).await;
+
+    let args = Args {
+        github_api: GithubApi::Graphql,
+        ..common::args(Forge::Github, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let mut results: Vec<Result<BusFactor, Failure>> = results.collect().await;
+    assert!(
+        matches!(results.pop(), Some(Err(Failure::RepoEmpty { repo })) if repo == "repo_3"),
+        "Repository without default branch should be empty"
+    );
+    let bus_factors = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+    let reported = bus_factors
+        .iter()
+        .map(|bus_factor| {
+            (
+                bus_factor.rank,
+                bus_factor.repo.as_str(),
+                bus_factor.contributors.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reported,
+        vec![
+            (1, "repo_0", vec!["alice".to_string()]),
+            (2, "repo_1", vec!["bob".to_string()]),
+        ],
+        "Repository with evenly spread commits should not have a bus factor"
+    );
+    assert_eq!(bus_factors[0].details.full_name, "owner/repo_0");
+    assert_eq!(bus_factors[0].details.stars, 30);
+    assert_eq!(
+        bus_factors[1].total_contributions, 5,
+        "History continuation should be counted"
+    );
+
+    let summary = stats.summary();
+    assert_eq!(summary.repos_analyzed, 3);
+    assert_eq!(
+        summary.resources["graphql"].requests, 2,
+        "Search and one batched history query should be sent"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn graphql_rate_limited() {
+    let server = MockServer::start().await;
+
+    mock_rate_limit(&server).await;
+    mock_search(&server).await;
+    mock_history(&server).await;
+    // Rate limited query is answered with 200 status
+    let body = json!({
+        "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }]
+    });
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_string_contains("repo0: repository"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-ratelimit-reset", Utc::now().timestamp().to_string().as_str())
+                .set_body_json(body),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let args = Args {
+        github_api: GithubApi::Graphql,
+        max_attempts: 1,
+        ..common::args(Forge::Github, Some(server.uri()))
+    };
+
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let results: Vec<Result<BusFactor, Failure>> = results.collect().await;
+    let analyzed = results.iter().filter(|result| result.is_ok()).count();
+    assert_eq!(analyzed, 2, "Rate limited history query should be re-issued");
+    assert_eq!(stats.summary().resources["graphql"].requests, 3);
+}
+
+async fn mock_rate_limit(server: &MockServer) {
+    let reset = Utc::now().timestamp() + 3600;
+    let resource = json!({ "limit": 5000, "remaining": 5000, "reset": reset });
+    let body = json!({
+        "resources": { "core": resource, "search": resource, "graphql": resource }
+    });
+    Mock::given(method("GET"))
+        .and(path("/rate_limit"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+async fn mock_search(server: &MockServer) {
+    let repos = vec![
+        repository(0, Some(history(&["alice", "alice", "alice"], None))),
+        repository(1, Some(history(&["bob", "carol"], Some("cursor_1")))),
+        repository(2, Some(history(&["dave", "erin", "frank", "grace"], None))),
+        repository(3, None),
+    ];
+    let body = json!({
+        "data": {
+            "rateLimit": rate_limit(),
+            "search": {
+                "pageInfo": { "endCursor": "search_1", "hasNextPage": true },
+                "nodes": repos
+            }
+        }
+    });
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_string_contains(format!("language:{} sort:stars", LANG)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+async fn mock_history(server: &MockServer) {
+    let body = json!({
+        "data": {
+            "rateLimit": rate_limit(),
+            "repo0": {
+                "defaultBranchRef": { "target": { "history": history(&["bob", "bob", "bob"], None) } }
+            }
+        }
+    });
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_string_contains("repo0: repository"))
+        .and(body_string_contains("cursor_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+/// Repository with given first page of history, or empty one.
+fn repository(index: u32, history: Option<Value>) -> Value {
+    let default_branch_ref = history.map(|history| json!({ "name": "main", "target": { "history": history } }));
+    json!({
+        "name": format!("repo_{}", index),
+        "nameWithOwner": format!("owner/repo_{}", index),
+        "owner": { "login": "owner" },
+        "url": format!("https://github.com/owner/repo_{}", index),
+        "stargazerCount": 30 - index,
+        "forkCount": 1,
+        "issues": { "totalCount": 2 },
+        "pushedAt": "2022-05-01T00:00:00Z",
+        "isArchived": false,
+        "isFork": false,
+        "defaultBranchRef": default_branch_ref
+    })
+}
+
+fn history(authors: &[&str], end_cursor: Option<&str>) -> Value {
+    let nodes = authors
+        .iter()
+        .map(|login| json!({ "author": { "name": login, "email": format!("{}@example.com", login), "user": { "login": login } } }))
+        .collect::<Vec<_>>();
+    json!({
+        "pageInfo": { "endCursor": end_cursor, "hasNextPage": end_cursor.is_some() },
+        "nodes": nodes
+    })
+}
+
+fn rate_limit() -> Value {
+    let reset_at = (Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+    json!({ "limit": 5000, "cost": 1, "remaining": 4990, "resetAt": reset_at })
+}
@@ -1,7 +1,10 @@
-use bus_factor::api::Sort;
+mod common;
+
+use bus_factor::api::{RepoDetails, Sort};
 use bus_factor::BusFactor;
 use bus_factor_app::args::Args;
 use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
 use chrono::Utc;
 use futures::StreamExt;
 use rand::Rng;
@@ -35,15 +38,23 @@ async fn happy_path_300() {
     let args = Args {
         language: LANG.to_string(),
         project_count: REPOS_COUNT,
-        api_token: None,
-        api_url: server.uri(),
-        threshold: 0.75,
-        max_repo_req: 1,
-        max_contrib_req: 10,
-        sort: Sort::HelpWantedIssues,
+        sort: Some(Sort::HelpWantedIssues),
+        ..common::args(Forge::Github, Some(server.uri()))
     };
 
-    let calculated_bus_factors: Vec<BusFactor> = calculate_bus_factor(args).await.unwrap().collect().await;
+    let (results, stats) = calculate_bus_factor(args).await.unwrap();
+    let calculated_bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+
+    let summary = stats.summary();
+    assert_eq!(summary.repos_searched, REPOS_COUNT);
+    assert_eq!(summary.repos_analyzed, REPOS_COUNT);
+    assert_eq!(summary.repos_reported, calculated_bus_factors.len() as u32);
+    assert_eq!(summary.repos_skipped, 0);
+    assert_eq!(
+        summary.resources["search"].requests,
+        (REPOS_COUNT / MAX_REPOS_PAGE) as u64
+    );
+    assert_eq!(summary.resources["core"].requests, REPOS_COUNT as u64);
 
     assert_eq!(
         expected_bus_factors.len(),
@@ -85,7 +96,7 @@ async fn mock_rate_limit(server: &MockServer) {
         .await;
 }
 
-async fn mock_repos<'a>(server: &MockServer, repos_count: u32, lang: String) {
+async fn mock_repos(server: &MockServer, repos_count: u32, lang: String) {
     for repo_page in 0..repos_count / MAX_REPOS_PAGE {
         let mut body = String::from(
             r#"{
@@ -98,11 +109,13 @@ async fn mock_repos<'a>(server: &MockServer, repos_count: u32, lang: String) {
             body.push_str(&format!(
                 r#"{{
                     "name": "repo_{}",
+                    "full_name": "owner_{}/repo_{}",
                     "owner": {{
                         "login": "owner_{}"
-                    }}
+                    }},
+                    "stargazers_count": {}
                 }}"#,
-                repo_index, repo_index
+                repo_index, repo_index, repo_index, repo_index, repo_index
             ));
             middle_coma(&mut body, repo_page_index, MAX_REPOS_PAGE - 1);
         }
@@ -150,7 +163,21 @@ async fn mock_contributors(
         let will_have_bus_factor = repo_index % bus_factor_divisor == 0;
         if will_have_bus_factor {
             body.push_str(&contribution_body(&login, 1000));
-            bus_factors.push_back(BusFactor::new(format!("repo_{}", repo_index), login, 0.77));
+            let details = RepoDetails {
+                owner: format!("owner_{}", repo_index),
+                full_name: format!("owner_{}/repo_{}", repo_index, repo_index),
+                stars: repo_index,
+                ..RepoDetails::default()
+            };
+            let bus_factor = BusFactor {
+                // Other contributors have 24 .. 1 contributions
+                total_contributions: 1000 + (1..repo_contributors_count).map(|i| 25 - i).sum::<u32>(),
+                contributor_count: repo_contributors_count,
+                ..BusFactor::new(format!("repo_{}", repo_index), 1, vec![login], 0.77)
+                    .with_rank(repo_index + 1)
+                    .with_details(details)
+            };
+            bus_factors.push_back(bus_factor);
         } else {
             body.push_str(&contribution_body(&login, user_contributions));
         };
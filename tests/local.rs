@@ -0,0 +1,202 @@
+mod common;
+
+use bus_factor::BusFactor;
+use bus_factor_app::args::Args;
+use bus_factor_app::calculate_bus_factor;
+use bus_factor_app::forge::Forge;
+use chrono::NaiveDate;
+use futures::StreamExt;
+use std::path::Path;
+use std::process::Command;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn local_git_repos() {
+    let repos_dir = std::env::temp_dir().join(format!("bus_factor_local_{}", rand::random::<u64>()));
+
+    // Alice committed under two emails, merged by `.mailmap`
+    let repo = init_repo(&repos_dir, "mailmap");
+    std::fs::write(
+        repo.join(".mailmap"),
+        "Alice <alice@new.example.com> <alice@old.example.com>\n",
+    )
+    .unwrap();
+    git(&repo, &["add", ".mailmap"], None);
+    for day in 1..=4 {
+        commit(&repo, "Alice <alice@old.example.com>", &format!("2022-03-0{}", day));
+        commit(&repo, "Alice <alice@new.example.com>", &format!("2022-03-1{}", day));
+    }
+    commit(&repo, "Bob <bob@example.com>", "2022-03-20");
+    commit(&repo, "Bob <bob@example.com>", "2022-03-21");
+
+    // Carol committed most, but before the counted date range
+    let repo = init_repo(&repos_dir, "since");
+    for day in 1..=9 {
+        commit(&repo, "Carol <carol@example.com>", &format!("2019-01-0{}", day));
+    }
+    for day in 1..=5 {
+        commit(&repo, "Alice <alice@example.com>", &format!("2022-02-0{}", day));
+    }
+    commit(&repo, "Bob <bob@example.com>", "2022-02-10");
+
+    // Commits spread among many authors
+    let repo = init_repo(&repos_dir, "spread");
+    for author in 1..=5 {
+        commit(
+            &repo,
+            &format!("Dev{} <dev{}@example.com>", author, author),
+            "2022-01-01",
+        );
+        commit(
+            &repo,
+            &format!("Dev{} <dev{}@example.com>", author, author),
+            "2022-01-02",
+        );
+    }
+
+    let args = Args {
+        language: "Rust".to_string(),
+        project_count: 3,
+        repos_dir: Some(repos_dir.clone()),
+        since: Some(NaiveDate::from_ymd(2021, 1, 1)),
+        // Local repositories are sorted by latest commit, their only order
+        ..common::args(Forge::Local, None)
+    };
+
+    let (results, _) = calculate_bus_factor(args).await.unwrap();
+    let bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+    std::fs::remove_dir_all(&repos_dir).unwrap();
+
+    let bus_factors: Vec<(String, u32, Vec<String>)> = bus_factors
+        .into_iter()
+        .map(|bus_factor| (bus_factor.repo, bus_factor.factor, bus_factor.contributors))
+        .collect();
+    assert_eq!(
+        bus_factors,
+        vec![
+            ("mailmap".to_string(), 1, vec!["alice@new.example.com".to_string()]),
+            ("since".to_string(), 1, vec!["alice@example.com".to_string()]),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn local_git_branch_until() {
+    let repos_dir = std::env::temp_dir().join(format!("bus_factor_local_{}", rand::random::<u64>()));
+
+    // Dave committed most, but only to default branch
+    let repo = init_repo(&repos_dir, "branch");
+    commit(&repo, "Alice <alice@example.com>", "2022-03-01");
+    git(&repo, &["branch", "release"], None);
+    for _ in 0..20 {
+        commit(&repo, "Dave <dave@example.com>", "2022-03-05");
+    }
+    git(&repo, &["checkout", "--quiet", "release"], None);
+    commit(&repo, "Alice <alice@example.com>", "2022-03-09");
+    // Bob committed most on the last counted day
+    for _ in 0..8 {
+        commit(&repo, "Bob <bob@example.com>", "2022-03-10");
+    }
+    // Carol committed most, but after the counted date range
+    for _ in 0..20 {
+        commit(&repo, "Carol <carol@example.com>", "2022-03-11");
+    }
+    git(&repo, &["checkout", "--quiet", "main"], None);
+
+    let args = Args {
+        project_count: 1,
+        repos_dir: Some(repos_dir.clone()),
+        branch: Some("release".to_string()),
+        until: Some(NaiveDate::from_ymd(2022, 3, 10)),
+        ..common::args(Forge::Local, None)
+    };
+
+    let (results, _) = calculate_bus_factor(args).await.unwrap();
+    let bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+    std::fs::remove_dir_all(&repos_dir).unwrap();
+
+    let bus_factors: Vec<(String, u32, Vec<String>)> = bus_factors
+        .into_iter()
+        .map(|bus_factor| (bus_factor.repo, bus_factor.factor, bus_factor.contributors))
+        .collect();
+    assert_eq!(
+        bus_factors,
+        vec![("branch".to_string(), 1, vec!["bob@example.com".to_string()])]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn local_git_bare_and_broken_repos() {
+    let repos_dir = std::env::temp_dir().join(format!("bus_factor_local_{}", rand::random::<u64>()));
+
+    let repo = init_repo(&repos_dir, "origin");
+    for _ in 0..3 {
+        commit(&repo, "Alice <alice@example.com>", "2022-03-01");
+    }
+    commit(&repo, "Bob <bob@example.com>", "2022-03-02");
+    git(&repos_dir, &["clone", "--quiet", "--bare", "origin", "bare.git"], None);
+    std::fs::remove_dir_all(&repo).unwrap();
+
+    // Repository whose `HEAD` is not a commit cannot be read
+    let repo = init_repo(&repos_dir, "broken");
+    commit(&repo, "Carol <carol@example.com>", "2022-03-03");
+    std::fs::write(repo.join("file"), "content").unwrap();
+    let output = Command::new("git")
+        .current_dir(&repo)
+        .args(["hash-object", "-w", "file"])
+        .output()
+        .unwrap();
+    std::fs::write(repo.join(".git").join("HEAD"), output.stdout).unwrap();
+
+    let mut analyzed = Vec::new();
+    // Broken repository is skipped, and bare repository is found both in directory and as directory itself
+    for dir in [repos_dir.clone(), repos_dir.join("bare.git")] {
+        let args = Args {
+            project_count: 2,
+            repos_dir: Some(dir),
+            ..common::args(Forge::Local, None)
+        };
+        let (results, _) = calculate_bus_factor(args).await.unwrap();
+        let bus_factors: Vec<BusFactor> = results.map(Result::unwrap).collect().await;
+        analyzed.extend(
+            bus_factors
+                .into_iter()
+                .map(|bus_factor| (bus_factor.repo, bus_factor.contributors)),
+        );
+    }
+    std::fs::remove_dir_all(&repos_dir).unwrap();
+
+    let bare = ("bare.git".to_string(), vec!["alice@example.com".to_string()]);
+    assert_eq!(analyzed, vec![bare.clone(), bare]);
+}
+
+fn init_repo(repos_dir: &Path, name: &str) -> std::path::PathBuf {
+    let repo = repos_dir.join(name);
+    std::fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "--quiet", "--initial-branch", "main"], None);
+    repo
+}
+
+fn commit(repo: &Path, author: &str, date: &str) {
+    let message = format!("Commit of {} on {}", author, date);
+    let author_arg = format!("--author={}", author);
+    git(
+        repo,
+        &["commit", "--quiet", "--allow-empty", &author_arg, "-m", &message],
+        Some(&format!("{}T12:00:00Z", date)),
+    );
+}
+
+fn git(repo: &Path, args: &[&str], date: Option<&str>) {
+    let mut command = Command::new("git");
+    command
+        .current_dir(repo)
+        .args(["-c", "user.name=Committer", "-c", "user.email=committer@example.com"])
+        .args(["-c", "commit.gpgsign=false"])
+        .args(args)
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+    if let Some(date) = date {
+        command.env("GIT_AUTHOR_DATE", date).env("GIT_COMMITTER_DATE", date);
+    }
+    let status = command.status().unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
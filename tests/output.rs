@@ -0,0 +1,133 @@
+use bus_factor::api::{Error, RepoDetails, RequestContext};
+use bus_factor::{BusFactor, Failure, RunSummary};
+use bus_factor_app::output::{FailureSummary, Output, OutputFormat};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Writer sharing written bytes with the test.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_output(format: OutputFormat, bus_factors: &[BusFactor]) -> String {
+    write_output_with_summary(format, bus_factors, false)
+}
+
+fn write_output_with_summary(format: OutputFormat, bus_factors: &[BusFactor], summary_in_output: bool) -> String {
+    let buffer = SharedBuffer::default();
+    let mut output =
+        Output::new(format, Box::new(buffer.clone()), false, false).with_summary_in_output(summary_in_output);
+    output.begin().unwrap();
+    for bus_factor in bus_factors {
+        output.write(bus_factor).unwrap();
+    }
+    let summary = RunSummary {
+        repos_requested: 2,
+        ..RunSummary::default()
+    };
+    output.finish(&summary).unwrap();
+    let written = buffer.0.lock().unwrap().clone();
+    String::from_utf8(written).unwrap()
+}
+
+fn bus_factor(index: u32) -> BusFactor {
+    let details = RepoDetails {
+        owner: "owner".to_string(),
+        full_name: format!("owner/repo_{}", index),
+        stars: index,
+        ..RepoDetails::default()
+    };
+    BusFactor::new(format!("repo_{}", index), 1, vec!["a, \"b\"".to_string()], 0.8)
+        .with_rank(index)
+        .with_details(details)
+}
+
+#[test]
+fn json_test() {
+    let json = write_output(OutputFormat::Json, &[bus_factor(1), bus_factor(2)]);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value.as_array().map(Vec::len), Some(2));
+    assert_eq!(value[1]["details"]["full_name"], "owner/repo_2");
+
+    let json = write_output(OutputFormat::Json, &[]);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value, serde_json::json!([]));
+}
+
+#[test]
+fn ndjson_test() {
+    let ndjson = write_output(OutputFormat::Ndjson, &[bus_factor(1), bus_factor(2)]);
+    let stars = ndjson
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["details"]["stars"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(stars, vec![1, 2]);
+}
+
+#[test]
+fn csv_test() {
+    let csv = write_output(OutputFormat::Csv, &[bus_factor(1)]);
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("repo,rank,full_name,owner,"));
+    assert!(lines[1].starts_with("repo_1,1,owner/repo_1,owner,"));
+    assert!(lines[1].contains(r#","a, ""b""",0.8,at-risk,"#));
+}
+
+#[test]
+fn markdown_test() {
+    let markdown = write_output(OutputFormat::Markdown, &[bus_factor(1)]);
+    let lines = markdown.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("| repo | rank | full_name |"));
+    assert!(lines[1].starts_with("| --- | --- | --- |"));
+    assert!(lines[2].starts_with("| repo_1 | 1 | owner/repo_1 |"));
+    assert!(lines[2].contains("| 0.8 | at-risk |"));
+
+    let markdown = write_output(OutputFormat::Markdown, &[bus_factor(1).with_reached(false)]);
+    assert!(markdown.lines().nth(2).unwrap().contains("| 0.8 | ok |"));
+}
+
+#[test]
+fn failure_summary_test() {
+    let mut summary = FailureSummary::default();
+    summary.add(&Failure::RepoEmpty {
+        repo: "repo_1".to_string(),
+    });
+    summary.add(&Failure::SearchPageFailed {
+        page: 2,
+        repos: 3,
+        error: Error::Timeout(RequestContext::default()),
+    });
+    assert_eq!(summary.failed_repos(), 4);
+    assert_eq!(summary.ratio(10), 0.4);
+    assert_eq!(
+        summary.to_string(),
+        "Failed repositories: 4 (repo-empty: 1, search-page-failed (timeout): 1)"
+    );
+}
+
+#[test]
+fn summary_in_output_test() {
+    let json = write_output_with_summary(OutputFormat::Json, &[bus_factor(1), bus_factor(2)], true);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["results"].as_array().map(Vec::len), Some(2));
+    assert_eq!(value["summary"]["repos_requested"], 2);
+
+    let ndjson = write_output_with_summary(OutputFormat::Ndjson, &[bus_factor(1)], true);
+    let last = ndjson.lines().last().unwrap();
+    let value: serde_json::Value = serde_json::from_str(last).unwrap();
+    assert_eq!(value["summary"]["repos_requested"], 2);
+
+    let csv = write_output_with_summary(OutputFormat::Csv, &[bus_factor(1)], true);
+    assert_eq!(csv.lines().count(), 2, "Summary is not written to CSV output");
+}